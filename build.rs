@@ -1,14 +1,49 @@
-#[cfg(windows)]
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+// only generate() is needed here, the rest is for the installer and tests
+#[allow(dead_code)]
+#[path = "src/iconset.rs"]
+mod iconset;
+
+const ICON_SOURCE: &str = "resources/app-icon.png";
+
 fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/iconset.rs");
+    println!("cargo:rerun-if-changed={ICON_SOURCE}");
+
+    // the PNG and the icons generated from it are everything a build needs at runtime
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("cargo always sets OUT_DIR"));
+    let resource_dir = out_dir.join("resources");
+    let icons =
+        iconset::generate(Path::new(ICON_SOURCE), &resource_dir).expect("Failed to generate icons");
+    println!(
+        "cargo:rustc-env=SPOTIKILL_RESOURCE_DIR={}",
+        resource_dir.display()
+    );
+
+    platform_main(&out_dir, &icons);
+}
+
+#[cfg(windows)]
+fn platform_main(out_dir: &Path, icons: &iconset::GeneratedIcons) {
+    // embed the generated .ico; windres is happy with forward slashes and they need no escaping
+    let rc_path = out_dir.join("tray-props.rc");
+    let ico_path = icons.ico.to_string_lossy().replace('\\', "/");
+    std::fs::write(&rc_path, format!("app-icon ICON \"{ico_path}\"\n"))
+        .expect("Failed to write tray-props.rc");
     windres::Build::new()
-        .compile("tray-props.rc")
+        .compile(rc_path)
         .expect("Failed to compile tray-props.rc");
 }
 
 #[cfg(all(not(windows), not(target_os = "macos")))]
-fn main() {
+fn platform_main(_out_dir: &Path, _icons: &iconset::GeneratedIcons) {
     println!("cargo:warning=Currently, this program is only guaranteed to work on Windows.")
 }
 
 #[cfg(target_os = "macos")]
-fn main() {}
+fn platform_main(_out_dir: &Path, _icons: &iconset::GeneratedIcons) {}
@@ -0,0 +1,172 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::constants::CARGO_PKG_NAME;
+
+/// The label launchd knows the LaunchAgent by.
+#[cfg(target_os = "macos")]
+pub const LAUNCH_AGENT_LABEL: &str = const_format::concatcp!("com.dablenparty.", CARGO_PKG_NAME);
+
+/// Checks whether spotikill starts at login.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn is_enabled() -> anyhow::Result<bool> {
+    Ok(entry_path()?.exists())
+}
+
+/// Makes spotikill start at login, or stops it from doing so. Only the current user is affected.
+///
+/// # Arguments
+///
+/// * `enabled` - Whether spotikill should start at login.
+///
+/// # Errors
+///
+/// Returns an error if the autostart entry cannot be written or deleted.
+pub fn set_enabled(enabled: bool) -> anyhow::Result<()> {
+    let path = entry_path()?;
+    if !enabled {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to delete {}", path.display()))
+            }
+            _ => Ok(()),
+        };
+    }
+
+    let exe = std::env::current_exe().context("Failed to get the path to spotikill")?;
+    enable_for(&exe)?;
+    Ok(())
+}
+
+/// Makes `exe` start at login for the current user. Unlike [`set_enabled`], this doesn't have to
+/// be called from the executable itself, so installers can use it.
+///
+/// # Arguments
+///
+/// * `exe` - The path to the spotikill executable.
+///
+/// # Returns
+///
+/// The path of the autostart entry.
+///
+/// # Errors
+///
+/// Returns an error if the autostart entry cannot be written.
+pub fn enable_for(exe: &Path) -> anyhow::Result<PathBuf> {
+    let path = entry_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    write_entry(&path, exe).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Gets the path of the shortcut in the Startup folder.
+#[cfg(windows)]
+fn entry_path() -> anyhow::Result<PathBuf> {
+    crate::shortcut::get_startup_shortcut_path(CARGO_PKG_NAME)
+}
+
+#[cfg(windows)]
+fn write_entry(path: &Path, exe: &Path) -> anyhow::Result<()> {
+    use windows::Win32::System::Com::{CoInitializeEx, COINIT_APARTMENTTHREADED};
+
+    // the shortcut can't be updated in place
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    crate::shortcut::check_shortcut(exe, path)?;
+    unsafe {
+        // succeeds without doing anything if the event loop already initialized COM
+        CoInitializeEx(None, COINIT_APARTMENTTHREADED)?;
+    }
+    crate::shortcut::install_shortcut(crate::aumid::get_aumid(), exe, path)?;
+    Ok(())
+}
+
+/// Gets the path of the LaunchAgent in `~/Library/LaunchAgents`.
+#[cfg(target_os = "macos")]
+fn entry_path() -> anyhow::Result<PathBuf> {
+    let base_dirs = directories::BaseDirs::new().context("Failed to get home directory")?;
+    Ok(base_dirs
+        .home_dir()
+        .join("Library")
+        .join("LaunchAgents")
+        .join(format!("{LAUNCH_AGENT_LABEL}.plist")))
+}
+
+#[cfg(target_os = "macos")]
+fn write_entry(path: &Path, exe: &Path) -> anyhow::Result<()> {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    // the agent isn't loaded now, launchd picks it up at the next login
+    let plist = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>Label</key>
+	<string>{LAUNCH_AGENT_LABEL}</string>
+	<key>ProgramArguments</key>
+	<array>
+		<string>{}</string>
+	</array>
+	<key>RunAtLoad</key>
+	<true/>
+</dict>
+</plist>
+"#,
+        escape(&exe.to_string_lossy())
+    );
+    fs::write(path, plist)?;
+    Ok(())
+}
+
+/// Gets the path of the XDG autostart entry, usually in `~/.config/autostart`.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn entry_path() -> anyhow::Result<PathBuf> {
+    let base_dirs = directories::BaseDirs::new().context("Failed to get home directory")?;
+    Ok(base_dirs
+        .config_dir()
+        .join("autostart")
+        .join(format!("{CARGO_PKG_NAME}.desktop")))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn write_entry(path: &Path, exe: &Path) -> anyhow::Result<()> {
+    // SEE: https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html
+    let mut exec = String::from('"');
+    for c in exe.to_string_lossy().chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            exec.push('\\');
+        }
+        exec.push(c);
+    }
+    exec.push('"');
+    // a literal backslash in a string value must be escaped once more
+    let exec = exec.replace('\\', "\\\\");
+
+    let entry = format!(
+        "[Desktop Entry]\n\
+        Type=Application\n\
+        Name={CARGO_PKG_NAME}\n\
+        Comment={}\n\
+        Exec={exec}\n\
+        Terminal=false\n\
+        X-GNOME-Autostart-enabled=true\n",
+        env!("CARGO_PKG_DESCRIPTION")
+    );
+    fs::write(path, entry)?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::{env, path::PathBuf, process};
+
+use anyhow::Context;
+use spotikill::constants::{CARGO_BINARY, CARGO_MANIFEST_DIR};
+
+use crate::{
+    error::InstallError,
+    options::{Options, Profile},
+};
+
+/// Gets the spotikill executable to install: the prebuilt one if there is one, otherwise a
+/// fresh build from this source tree.
+///
+/// # Errors
+///
+/// Returns an error if cargo fails or the executable doesn't exist.
+pub fn build_or_locate(options: &Options) -> Result<PathBuf, InstallError> {
+    let exe = match &options.from_binary {
+        Some(binary) => binary.clone(),
+        None => build_with_cargo(options.profile).map_err(InstallError::Build)?,
+    };
+    if !exe.is_file() {
+        return Err(InstallError::BinaryNotFound(exe));
+    }
+    Ok(exe)
+}
+
+fn build_with_cargo(profile: Profile) -> anyhow::Result<PathBuf> {
+    let mut cargo = process::Command::new(CARGO_BINARY);
+    cargo
+        .args(["build", "--bin", env!("CARGO_PKG_NAME")])
+        .current_dir(CARGO_MANIFEST_DIR);
+    if profile == Profile::Release {
+        cargo.arg("--release");
+    }
+    println!("Running {cargo:?}");
+    let status = cargo.status().context("Failed to spawn cargo.")?;
+    if !status.success() {
+        anyhow::bail!("Failed to build executable: {status}");
+    }
+
+    let target_dir = env::var_os("CARGO_TARGET_DIR").map_or_else(
+        || PathBuf::from(CARGO_MANIFEST_DIR).join("target"),
+        PathBuf::from,
+    );
+    let target_dir = if target_dir.is_absolute() {
+        target_dir
+    } else {
+        PathBuf::from(CARGO_MANIFEST_DIR).join(target_dir)
+    };
+    Ok(target_dir.join(profile.dir_name()).join(format!(
+        "{}{}",
+        env!("CARGO_PKG_NAME"),
+        env::consts::EXE_SUFFIX
+    )))
+}
@@ -0,0 +1,124 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// Why the installer failed. Each kind exits with its own code, see [`InstallError::exit_code`].
+#[derive(Debug)]
+pub enum InstallError {
+    /// The command line is wrong.
+    Usage(anyhow::Error),
+    /// The executable to install doesn't exist.
+    BinaryNotFound(PathBuf),
+    /// cargo couldn't build spotikill.
+    Build(anyhow::Error),
+    /// The `[package.metadata.bundle]` settings in `Cargo.toml` are invalid.
+    BundleConfig(anyhow::Error),
+    /// Something can't be written where spotikill is installed.
+    Destination {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The installed executable can't be replaced because it is running.
+    InUse(PathBuf),
+    /// The system refused, e.g. COM, the registry or `hdiutil`.
+    System(anyhow::Error),
+    Other(anyhow::Error),
+}
+
+impl InstallError {
+    /// The process exit code for this kind of failure, so scripts can tell them apart.
+    pub const fn exit_code(&self) -> i32 {
+        match self {
+            Self::Other(_) => 1,
+            Self::Usage(_) => 2,
+            Self::BinaryNotFound(_) => 3,
+            Self::Build(_) => 4,
+            Self::BundleConfig(_) => 5,
+            Self::Destination { .. } => 6,
+            Self::InUse(_) => 7,
+            Self::System(_) => 8,
+        }
+    }
+}
+
+impl fmt::Display for InstallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = env!("CARGO_PKG_NAME");
+        match self {
+            Self::Usage(e) => write!(f, "{e:#}\n\nRun with --help to see the options"),
+            Self::BinaryNotFound(path) => write!(
+                f,
+                "{name} executable not found at {}; build it first or run with --from-binary \
+                 <path>",
+                path.display()
+            ),
+            Self::Build(e) => write!(
+                f,
+                "{e:#}; fix the build or run with --from-binary <path> to install a prebuilt \
+                 {name}"
+            ),
+            Self::BundleConfig(e) => {
+                write!(f, "{e:#}; fix [package.metadata.bundle] in Cargo.toml")
+            }
+            Self::Destination { path, source } => {
+                let hint = if cfg!(target_os = "macos") {
+                    "run with --user or --prefix <dir> to install somewhere you can write to"
+                } else {
+                    "run with --prefix <dir> to install somewhere you can write to"
+                };
+                write!(f, "Can't write to {}: {source}; {hint}", path.display())
+            }
+            Self::InUse(path) => write!(
+                f,
+                "{} is in use; quit {name} from the tray and try again",
+                path.display()
+            ),
+            Self::System(e) | Self::Other(e) => write!(f, "{e:#}"),
+        }
+    }
+}
+
+impl std::error::Error for InstallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Destination { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Anything not classified where it happened.
+impl From<anyhow::Error> for InstallError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Other(e)
+    }
+}
+
+#[cfg(windows)]
+impl From<spotikill::shortcut::ShortcutError> for InstallError {
+    fn from(e: spotikill::shortcut::ShortcutError) -> Self {
+        use spotikill::shortcut::ShortcutError;
+
+        match e {
+            ShortcutError::ExeNotFound(path) => Self::BinaryNotFound(path),
+            e => Self::System(e.into()),
+        }
+    }
+}
+
+/// Classifies a failure to write to `path`: in use if another process has the file open,
+/// otherwise a problem with the destination. Failures other than I/O errors are left as they are.
+pub fn write_error(path: &Path, e: anyhow::Error) -> InstallError {
+    match e.downcast::<io::Error>() {
+        // ERROR_SHARING_VIOLATION, which is what Windows says when writing a running executable
+        Ok(source) if cfg!(windows) && source.raw_os_error() == Some(32) => {
+            InstallError::InUse(path.to_path_buf())
+        }
+        Ok(source) => InstallError::Destination {
+            path: path.to_path_buf(),
+            source,
+        },
+        Err(e) => InstallError::Other(e),
+    }
+}
@@ -1,174 +1,29 @@
 #![cfg(target_os = "macos")]
 
 use std::{
-    env,
-    fmt::Write,
-    fs, io,
+    env, fs, io,
     path::{Path, PathBuf},
     process,
 };
 
 use anyhow::Context;
 use const_format::concatcp;
-use spotikill::constants::{CARGO_BINARY, CARGO_PKG_NAME, CARGO_PKG_VERSION};
-
-fn compile_with_cargo() -> io::Result<()> {
-    #[cfg(debug_assertions)]
-    const CARGO_ARGS: &[&str] = &["build", "--bin", env!("CARGO_PKG_NAME")];
-    #[cfg(not(debug_assertions))]
-    const CARGO_ARGS: &[&str] = &["build", "--release", "--bin", env!("CARGO_PKG_NAME")];
-    // the package name constant is modified in debug mode to include "-debug"
-
-    #[cfg(debug_assertions)]
-    {
-        println!("cargo binary: {CARGO_BINARY:?}");
-        println!("cargo args: {CARGO_ARGS:?}");
-        println!("waiting for 3 seconds so you can read this...");
-        std::thread::sleep(std::time::Duration::from_secs(3));
-    }
-
-    let status = process::Command::new(CARGO_BINARY)
-        .args(CARGO_ARGS)
-        .status()?;
-
-    if !status.success() {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            "cargo failed to build",
-        ));
-    };
-
-    #[cfg(debug_assertions)]
-    {
-        println!("in debug mode, renaming executable...");
-        let executable_path = PathBuf::from(concatcp!("target/debug/", env!("CARGO_PKG_NAME")));
-        let new_path = executable_path.with_file_name(CARGO_PKG_NAME);
-        fs::rename(executable_path, new_path)?;
-    }
-
-    Ok(())
-}
-
-fn construct_package_skeleton(package_root: &Path) -> io::Result<()> {
-    let contents = package_root.join("Contents");
-    fs::create_dir_all(contents.join("MacOS"))?;
-    fs::create_dir_all(contents.join("Resources"))?;
-    Ok(())
-}
-
-fn write_info_plist(package_root: &Path) -> anyhow::Result<()> {
-    const PLIST_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
-<plist version="1.0">
-<dict>
-"#;
-    const PLIST_FOOTER: &str = "</dict>\n</plist>";
-
-    // writing into a string buffer to limit writes to disk
-    let mut plist_contents = String::from(PLIST_HEADER);
-    write!(
-        plist_contents,
-        "\t<key>CFBundleIdentifier</key>\n\
-    \t<string>com.dablenparty.{CARGO_PKG_NAME}</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleName</key>\n\
-    \t<string>{CARGO_PKG_NAME}</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleExecutable</key>\n\
-    \t<string>{CARGO_PKG_NAME}</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleIconFile</key>\n\
-    \t<string>app-icon.icns</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleIconName</key>\n\
-    \t<string>app-icon</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleVersion</key>\n\
-    \t<string>{CARGO_PKG_VERSION}</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleShortVersionString</key>\n\
-    \t<string>{CARGO_PKG_VERSION}</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundlePackageType</key>\n\
-    \t<string>APPL</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleInfoDictionaryVersion</key>\n\
-    \t<string>6.0</string>\n"
-    )?;
-
-    // TODO: research what features I'm using
-    // write!(
-    //     plist_contents,
-    //     "\t<key>LSMinimumSystemVersion</key>\n\
-    // \t<string>10.15</string>\n"
-    // )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleDevelopmentRegion</key>\n\
-    \t<string>en</string>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>CFBundleSupportedPlatforms</key>\n\
-    \t<array>\n\
-    \t\t<string>MacOSX</string>\n\
-    \t</array>\n"
-    )?;
-
-    // hide from dock, but only in release mode
-    #[cfg(not(debug_assertions))]
-    write!(
-        plist_contents,
-        "\t<key>LSUIElement</key>\n\
-    \t<true/>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>NSHighResolutionCapable</key>\n\
-    \t<true/>\n"
-    )?;
-
-    write!(
-        plist_contents,
-        "\t<key>NSMainNibFile</key>\n\
-    \t<string></string>\n"
-    )?;
-
-    write!(plist_contents, "{PLIST_FOOTER}")?;
-
-    // write to file
-    let plist_path = package_root.join("Contents").join("Info.plist");
-    fs::write(plist_path, plist_contents)?;
+use spotikill::constants::CARGO_PKG_NAME;
+use spotikill::{
+    autostart,
+    bundle::{AppBundle, InfoPlist},
+    install::{
+        bundle_config, seed_user_config, write_default_config, ChangeKind, FileChange,
+        DEFAULT_CONFIG_FILE_NAME, RESOURCES,
+    },
+};
 
-    Ok(())
-}
+use crate::{
+    binary::build_or_locate,
+    error::{write_error, InstallError},
+    options::{Options, Package},
+    tracking,
+};
 
 fn make_dmg_package(src: &Path, dest: &Path) -> anyhow::Result<()> {
     const COMMAND_NAME: &str = "hdiutil";
@@ -197,49 +52,147 @@ fn make_dmg_package(src: &Path, dest: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn install() -> anyhow::Result<()> {
-    const PACKAGE_TARGET: &str = concatcp!("target/macos/", CARGO_PKG_NAME, ".app");
+/// The directory the bundle is assembled in. It is deleted when this is dropped, so nothing is
+/// left behind whether installing succeeds or fails.
+struct StagingDir(PathBuf);
+
+impl StagingDir {
+    /// Creates an empty staging directory, deleting whatever an earlier run left in it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the old directory can't be deleted or the new one can't be created.
+    fn create(path: PathBuf) -> anyhow::Result<Self> {
+        match fs::remove_dir_all(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to delete {}", path.display()))
+            }
+        }
+        fs::create_dir_all(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self(path))
+    }
+}
 
-    let package_target = PathBuf::from(PACKAGE_TARGET);
-    if package_target.exists() {
-        println!(
-            "Found existing package at {}, removing...",
-            package_target.display()
-        );
-        fs::remove_dir_all(&package_target)?;
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        // a leftover staging directory is cleared by the next run anyway
+        let _ = fs::remove_dir_all(&self.0);
     }
+}
+
+/// Gets the directory to install the app into, `/Applications` unless there is a prefix.
+fn applications_dir(options: &Options) -> PathBuf {
+    options
+        .prefix
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("/Applications"))
+}
+
+/// Makes the `Info.plist` from the settings in `Cargo.toml` and checks it.
+fn info_plist() -> anyhow::Result<InfoPlist> {
+    let info_plist = InfoPlist::for_spotikill(&bundle_config()?);
+    info_plist.validate()?;
+    Ok(info_plist)
+}
 
-    construct_package_skeleton(&package_target)?;
-    compile_with_cargo()?;
-    let cargo_profile = if cfg!(debug_assertions) {
-        "debug"
+/// Compares a freshly built bundle to the installed one, file by file.
+///
+/// # Returns
+///
+/// What installing `staged` would do to each file, with paths in `installed`.
+///
+/// # Errors
+///
+/// Returns an error if either bundle can't be read.
+fn compare_bundles(staged: &AppBundle, installed: &AppBundle) -> anyhow::Result<Vec<FileChange>> {
+    let mut changes = Vec::new();
+    for file in staged.files()? {
+        let target = installed.root().join(file.strip_prefix(staged.root())?);
+        let contents =
+            fs::read(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+        let kind = match fs::read(&target) {
+            Ok(existing) if existing == contents => ChangeKind::Unchanged,
+            Ok(_) => ChangeKind::Updated,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ChangeKind::Created,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", target.display()))
+            }
+        };
+        changes.push(FileChange::new(target, kind));
+    }
+    if installed.root().exists() {
+        for file in installed.files()? {
+            if !changes.iter().any(|change| change.path == file) {
+                changes.push(FileChange::new(file, ChangeKind::Removed));
+            }
+        }
+    }
+    Ok(changes)
+}
+
+pub fn install(options: &Options) -> Result<Vec<FileChange>, InstallError> {
+    // catch bad settings before spending time on a build
+    let info_plist = info_plist().map_err(InstallError::BundleConfig)?;
+
+    let previous = tracking::load_previous()?;
+    let executable = build_or_locate(options)?;
+    // assembled outside the source tree, which may not even exist with --from-binary
+    let staging_dir =
+        StagingDir::create(env::temp_dir().join(concatcp!(CARGO_PKG_NAME, "-installer")))?;
+    let bundle = AppBundle::new(&staging_dir.0, CARGO_PKG_NAME);
+    // the bundle uses the .icns and the tray uses the .png, so every format goes in
+    bundle.build(&executable, &info_plist, &RESOURCES)?;
+    write_default_config(&bundle.resources_dir())?;
+
+    if options.package == Some(Package::Dmg) {
+        let out_dir = match &options.prefix {
+            Some(prefix) => prefix.clone(),
+            None => env::current_dir().context("Failed to get the current directory")?,
+        };
+        fs::create_dir_all(&out_dir).map_err(|source| InstallError::Destination {
+            path: out_dir.clone(),
+            source,
+        })?;
+        let dmg_path = out_dir.join(concatcp!(CARGO_PKG_NAME, ".dmg"));
+        let kind = if dmg_path.exists() {
+            ChangeKind::Updated
+        } else {
+            ChangeKind::Created
+        };
+        make_dmg_package(bundle.root(), &dmg_path).map_err(InstallError::System)?;
+        println!("Created {}", dmg_path.display());
+        // a package isn't an install, so there is nothing to record
+        return Ok(vec![FileChange::new(dmg_path, kind)]);
+    }
+
+    let applications_dir = applications_dir(options);
+    let installed = AppBundle::new(&applications_dir, CARGO_PKG_NAME);
+    let mut changes = compare_bundles(&bundle, &installed)?;
+    if changes
+        .iter()
+        .all(|change| change.kind == ChangeKind::Unchanged)
+    {
+        println!("{} is up to date", installed.root().display());
     } else {
-        "release"
-    };
-    fs::copy(
-        PathBuf::from(format!("target/{cargo_profile}/{CARGO_PKG_NAME}")),
-        package_target
-            .join("Contents")
-            .join("MacOS")
-            .join(CARGO_PKG_NAME),
-    )?;
-    write_info_plist(&package_target)?;
-    // copy icon
-    fs::copy(
-        PathBuf::from("resources/app-icon.icns"),
-        package_target
-            .join("Contents")
-            .join("Resources")
-            .join("app-icon.icns"),
-    )?;
-
-    // TODO: just install it into /Applications right here instead of making a DMG
-    // TODO: make icon set
-    // SEE: https://gist.github.com/jamieweavis/b4c394607641e1280d447deed5fc85fc
-    // ^ tells you how to convert directory to icns file
-    // create DMG
-    let dmg_path = PathBuf::from(format!("target/macos/{CARGO_PKG_NAME}.dmg"));
-    make_dmg_package(&package_target, &dmg_path)?;
+        bundle
+            .install_into(&applications_dir)
+            .map_err(|e| write_error(&applications_dir, e))?;
+        println!("Installed to {}", installed.root().display());
+    }
 
-    Ok(())
+    let agent_path = autostart::enable_for(&installed.executable())?;
+    println!("Registered LaunchAgent at {}", agent_path.display());
+    changes.push(tracking::change_since(previous.as_ref(), &agent_path)?);
+
+    let removed = tracking::record(installed.root(), &changes, previous.as_ref())?;
+    changes.extend(removed);
+    // the config belongs to the user, so it isn't recorded and uninstalling keeps it
+    let default_config = installed.resources_dir().join(DEFAULT_CONFIG_FILE_NAME);
+    changes.extend(
+        seed_user_config(&default_config)?.map(|path| FileChange::new(path, ChangeKind::Created)),
+    );
+    Ok(changes)
 }
@@ -1,7 +1,86 @@
+use std::process;
+
+use anyhow::Context;
+use spotikill::constants::{CARGO_BINARY, CARGO_MANIFEST_DIR};
+use spotikill::install::{ChangeKind, FileChange};
+
+use crate::{
+    error::InstallError,
+    options::{Options, Profile, USAGE},
+};
+
+mod binary;
+mod error;
 #[cfg_attr(windows, path = "windows.rs")]
 #[cfg_attr(target_os = "macos", path = "macos.rs")]
 mod installer_core;
+mod options;
+mod tracking;
+
+/// Everything installed is named after the installer's own profile, e.g. `spotikill-debug` for
+/// debug builds, so installing another profile is left to an installer built with it.
+fn rerun_with_profile(options: &Options, args: &[String]) -> Result<(), InstallError> {
+    let profile = options.profile.dir_name();
+    if options.from_binary.is_some() {
+        return Err(InstallError::Usage(anyhow::anyhow!(
+            "This installer can only install {} builds with --from-binary, use one built with \
+             --profile {profile}",
+            Profile::current().dir_name(),
+        )));
+    }
+
+    let mut cargo = process::Command::new(CARGO_BINARY);
+    cargo
+        .args(["run", "--features", "installer", "--bin", "installer"])
+        .current_dir(CARGO_MANIFEST_DIR);
+    if options.profile == Profile::Release {
+        cargo.arg("--release");
+    }
+    cargo.arg("--").args(args);
+    println!("Switching to the {profile} installer: {cargo:?}");
+    let status = cargo
+        .status()
+        .context("Failed to spawn cargo.")
+        .map_err(InstallError::Build)?;
+    process::exit(status.code().unwrap_or(1));
+}
+
+fn print_summary(changes: &[FileChange]) {
+    let unchanged = changes
+        .iter()
+        .filter(|change| change.kind == ChangeKind::Unchanged)
+        .count();
+    if unchanged == changes.len() {
+        println!("\nEverything was already up to date");
+        return;
+    }
+    println!(
+        "\nChanged {} files, {unchanged} were already up to date:",
+        changes.len() - unchanged
+    );
+    for change in changes {
+        println!("  {:<9}  {}", change.kind, change.path.display());
+    }
+}
+
+fn run() -> Result<(), InstallError> {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return Ok(());
+    }
+    let options = Options::parse(&args)?;
+    if options.profile != Profile::current() {
+        return rerun_with_profile(&options, &args);
+    }
+    let changes = installer_core::install(&options)?;
+    print_summary(&changes);
+    Ok(())
+}
 
-fn main() -> anyhow::Result<()> {
-    installer_core::install()
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {e}");
+        process::exit(e.exit_code());
+    }
 }
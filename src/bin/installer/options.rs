@@ -0,0 +1,194 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::Context;
+
+use crate::error::InstallError;
+
+pub const USAGE: &str = "\
+Usage: installer [options]
+
+Options:
+  --prefix <dir>         Install into <dir>, or put the package there
+                         (default: %LOCALAPPDATA%\\Programs on Windows, /Applications on macOS)
+  --from-binary <path>   Install a prebuilt spotikill instead of building it with cargo
+  --profile <profile>    Build and install a debug or release build
+                         (default: the profile the installer was built with)
+  --package dmg          Build a DMG instead of installing (macOS only)
+  --user                 Same as --prefix ~/Applications (macOS only)
+  --no-startup           Don't start at login (Windows only)
+  -h, --help             Print this message
+
+Exit codes:
+  1  Unexpected error
+  2  Invalid command line
+  3  The executable to install wasn't found
+  4  cargo couldn't build spotikill
+  5  Invalid [package.metadata.bundle] in Cargo.toml
+  6  The install location can't be written to
+  7  spotikill is running and can't be replaced
+  8  The system refused (COM, the registry or hdiutil)";
+
+/// A package to build instead of installing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Package {
+    Dmg,
+}
+
+impl FromStr for Package {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dmg" => Ok(Self::Dmg),
+            _ => anyhow::bail!("Unknown package format: {s}"),
+        }
+    }
+}
+
+/// The cargo profile of the installed spotikill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Debug,
+    Release,
+}
+
+impl Profile {
+    /// The profile this installer was built with. Everything it installs is named after it, e.g.
+    /// `spotikill-debug` for debug builds.
+    pub const fn current() -> Self {
+        if cfg!(debug_assertions) {
+            Self::Debug
+        } else {
+            Self::Release
+        }
+    }
+
+    /// The name of the profile's directory in `target`.
+    pub const fn dir_name(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Release => "release",
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+impl FromStr for Profile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "debug" | "dev" => Ok(Self::Debug),
+            "release" => Ok(Self::Release),
+            _ => anyhow::bail!("Unknown profile: {s}, use debug or release"),
+        }
+    }
+}
+
+/// What the installer was asked to do.
+#[derive(Debug, Default)]
+pub struct Options {
+    pub package: Option<Package>,
+    /// Where to install, or where to put the package. Always absolute.
+    pub prefix: Option<PathBuf>,
+    /// A prebuilt executable to install instead of building one.
+    pub from_binary: Option<PathBuf>,
+    pub profile: Profile,
+    /// Skip the Startup shortcut.
+    #[cfg(windows)]
+    pub no_startup: bool,
+}
+
+impl Options {
+    /// Parses and validates the command line, without the program name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an argument is unknown, is missing its value or names an invalid path.
+    pub fn parse(args: &[String]) -> Result<Self, InstallError> {
+        let mut options = Self::parse_args(args).map_err(InstallError::Usage)?;
+        if let Some(prefix) = &options.prefix {
+            options.prefix = Some(validate_prefix(prefix).map_err(InstallError::Usage)?);
+        }
+        if let Some(binary) = &options.from_binary {
+            validate_binary(binary)?;
+        }
+        Ok(options)
+    }
+
+    fn parse_args(args: &[String]) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        #[cfg(target_os = "macos")]
+        let mut user = false;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().with_context(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--package" => options.package = Some(value()?.parse()?),
+                "--prefix" => options.prefix = Some(PathBuf::from(value()?)),
+                "--from-binary" => options.from_binary = Some(PathBuf::from(value()?)),
+                "--profile" => options.profile = value()?.parse()?,
+                #[cfg(target_os = "macos")]
+                "--user" => user = true,
+                #[cfg(windows)]
+                "--no-startup" => options.no_startup = true,
+                _ => anyhow::bail!("Unknown argument: {arg}"),
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        if user {
+            anyhow::ensure!(
+                options.prefix.is_none(),
+                "--user and --prefix can't be used together"
+            );
+            let base_dirs = directories::BaseDirs::new().context("Failed to get home directory")?;
+            options.prefix = Some(base_dirs.home_dir().join("Applications"));
+        }
+        Ok(options)
+    }
+}
+
+/// Makes the prefix absolute and checks it can be a directory.
+fn validate_prefix(prefix: &Path) -> anyhow::Result<PathBuf> {
+    anyhow::ensure!(!prefix.as_os_str().is_empty(), "--prefix is empty");
+    let prefix = if prefix.is_absolute() {
+        prefix.to_owned()
+    } else {
+        std::env::current_dir()
+            .context("Failed to get the current directory")?
+            .join(prefix)
+    };
+    anyhow::ensure!(
+        !prefix.exists() || prefix.is_dir(),
+        "--prefix {} exists and isn't a directory",
+        prefix.display()
+    );
+    Ok(prefix)
+}
+
+/// Checks the prebuilt executable exists.
+fn validate_binary(binary: &Path) -> Result<(), InstallError> {
+    if !binary.is_file() {
+        return Err(InstallError::BinaryNotFound(binary.to_path_buf()));
+    }
+    #[cfg(windows)]
+    if !binary
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+    {
+        return Err(InstallError::Usage(anyhow::anyhow!(
+            "--from-binary {} isn't an .exe",
+            binary.display()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use spotikill::install::{ChangeKind, FileChange};
+use spotikill::manifest::{FileStatus, InstallManifest};
+
+/// Loads the manifest of the previous install, if there is one, and warns about local changes
+/// to it.
+///
+/// # Errors
+///
+/// Returns an error if the manifest or the files it lists can't be read.
+pub fn load_previous() -> anyhow::Result<Option<InstallManifest>> {
+    let Some(previous) = InstallManifest::load()? else {
+        return Ok(None);
+    };
+    let current = spotikill::update::current_version();
+    if previous.version == current {
+        println!("Re-installing {current}, files that are up to date are left alone");
+    } else {
+        println!("Upgrading {} to {current}", previous.version);
+    }
+    for file in previous
+        .modified_files()
+        .context("Failed to check the installed files")?
+    {
+        eprintln!("{} was changed since it was installed", file.path.display());
+    }
+    Ok(Some(previous))
+}
+
+/// Works out what happened to a file that is always rewritten, e.g. a shortcut, by comparing it
+/// to the previous install.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read.
+pub fn change_since(previous: Option<&InstallManifest>, path: &Path) -> anyhow::Result<FileChange> {
+    let kind = match previous.and_then(|previous| previous.file(path)) {
+        Some(file) => match file
+            .status()
+            .with_context(|| format!("Failed to read {}", path.display()))?
+        {
+            FileStatus::Unchanged => ChangeKind::Unchanged,
+            FileStatus::Modified | FileStatus::Missing => ChangeKind::Updated,
+        },
+        None => ChangeKind::Created,
+    };
+    Ok(FileChange::new(path.to_path_buf(), kind))
+}
+
+/// Records this install in the manifest, then deletes whatever the previous install had that
+/// this one doesn't, so upgrades don't leave stale files behind.
+///
+/// # Arguments
+///
+/// * `install_dir` - Where spotikill was installed.
+/// * `changes` - Every file this install is made of, and the ones it removed. The user's config
+///   doesn't belong here.
+/// * `previous` - The manifest of the previous install.
+///
+/// # Returns
+///
+/// The deleted files.
+///
+/// # Errors
+///
+/// Returns an error if the manifest can't be written or a stale file can't be deleted.
+pub fn record(
+    install_dir: &Path,
+    changes: &[FileChange],
+    previous: Option<&InstallManifest>,
+) -> anyhow::Result<Vec<FileChange>> {
+    let files: Vec<PathBuf> = changes
+        .iter()
+        .filter(|change| change.kind != ChangeKind::Removed)
+        .map(|change| change.path.clone())
+        .collect();
+    let mut removed = Vec::new();
+    if let Some(previous) = previous {
+        let report = previous.remove_files(&files)?;
+        for path in &report.kept_modified {
+            eprintln!(
+                "Kept {}, it isn't part of this install but was changed since it was installed",
+                path.display()
+            );
+        }
+        removed.extend(
+            report
+                .removed
+                .into_iter()
+                .map(|path| FileChange::new(path, ChangeKind::Removed)),
+        );
+    }
+
+    let manifest_path = InstallManifest::new(install_dir, &files)?.save()?;
+    println!("Recorded the install in {}", manifest_path.display());
+    Ok(removed)
+}
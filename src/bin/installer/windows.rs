@@ -1,134 +1,126 @@
 #![cfg(target_os = "windows")]
 
-use std::path::{Path, PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context;
 use spotikill::aumid::get_aumid;
 use spotikill::constants::CARGO_PKG_NAME;
-use windows::core::{ComInterface, HSTRING};
-use windows::Win32::Storage::EnhancedStorage::PKEY_AppUserModel_ID;
-use windows::Win32::System::Com::StructuredStorage::{
-    InitPropVariantFromStringAsVector, PropVariantClear,
+use spotikill::install::{
+    copy_if_changed, copy_resources, seed_user_config, write_default_config, ChangeKind, FileChange,
 };
-use windows::Win32::System::Com::{
-    CoCreateInstance, CoInitializeEx, IPersistFile, CLSCTX_LOCAL_SERVER, COINIT_APARTMENTTHREADED,
+use spotikill::resources::RESOURCE_DIR_NAME;
+use spotikill::shortcut::{
+    check_shortcut, get_start_menu_shortcut_path, get_startup_shortcut_path, install_shortcut,
 };
-use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
-use windows::Win32::UI::Shell::{IShellLinkW, ShellLink};
-
-fn get_shortcut_path(shortcut_name: &str) -> anyhow::Result<PathBuf> {
-    const START_MENU_PATH_COMPONENTS: &str = r"Microsoft\Windows\Start Menu\Programs\Startup";
-    let app_data_folder = {
-        let base_dirs = directories::BaseDirs::new().context("Failed to get home directory")?;
-        base_dirs.config_dir().to_path_buf()
-    };
-    let shortcut_path = app_data_folder
-        .join(START_MENU_PATH_COMPONENTS)
-        .join(shortcut_name)
-        .with_extension("lnk");
-    Ok(shortcut_path)
-}
-
-/// Installs a shortcut for a given executable at the given path.
-///
-/// # Arguments
-///
-/// * `aumid` - The ApplicationUserModel ID of the app to link to.
-/// * `exe_path` - The path to the executable to link to.
-/// * `shortcut_path` - The path to the shortcut to create.
-///
-/// # Safety
-///
-/// This function is unsafe because it calls the Windows API directly. It is safe to call if the
-/// arguments are valid, which is ensured by the function via assertions.
-///
-/// # Panics
-///
-/// Panics if the shortcut path already exists, is not an `.lnk` file, or if the executable path
-/// does _not_ exist.
-unsafe fn install_shortcut(
-    aumid: &str,
-    exe_path: &Path,
-    shortcut_path: &Path,
-) -> windows::core::Result<()> {
-    assert!(shortcut_path.extension().unwrap_or_default() == "lnk");
-    assert!(!shortcut_path.exists());
-    assert!(exe_path.exists());
-    let shell_link_interface: IShellLinkW =
-        CoCreateInstance(&ShellLink, None, CLSCTX_LOCAL_SERVER)?;
-    shell_link_interface.SetPath(&HSTRING::from(exe_path))?;
-    // TODO: research what the arguments are for
-    shell_link_interface.SetArguments(&HSTRING::from(""))?;
-
-    let property_store_interface: IPropertyStore = shell_link_interface.cast()?;
-    let mut propvar = InitPropVariantFromStringAsVector(&HSTRING::from(aumid))?;
-    property_store_interface.SetValue(&PKEY_AppUserModel_ID, &propvar)?;
-    property_store_interface.Commit()?;
-    // PROPVARIANT doesn't implement Drop, it must be freed manully
-    PropVariantClear(&mut propvar)?;
+use spotikill::uninstall;
+use windows::Win32::System::Com::{CoInitializeEx, COINIT_APARTMENTTHREADED};
 
-    let saveable_shortcut: IPersistFile = shell_link_interface.cast()?;
-    // second param says to use the first param as the save path
-    saveable_shortcut.Save(&HSTRING::from(shortcut_path), true)?;
+use crate::{
+    binary::build_or_locate,
+    error::{write_error, InstallError},
+    options::Options,
+    tracking,
+};
 
-    Ok(())
+/// Gets the install directory, `<prefix>\spotikill`. The prefix defaults to
+/// `%LOCALAPPDATA%\Programs`.
+fn install_dir(options: &Options) -> anyhow::Result<PathBuf> {
+    let prefix = match &options.prefix {
+        Some(prefix) => prefix.clone(),
+        None => directories::BaseDirs::new()
+            .context("Could not load home directory")?
+            .data_local_dir()
+            .join("Programs"),
+    };
+    Ok(prefix.join(CARGO_PKG_NAME))
 }
 
-pub fn install() -> anyhow::Result<()> {
-    const AUMID: &str = get_aumid();
-    #[cfg(not(debug_assertions))]
-    {
-        const INSTALL_COMMAND: [&str; 7] = [
-            "cargo",
-            "install",
-            "--force",
-            "--path",
-            spotikill::constants::CARGO_MANIFEST_DIR,
-            "--bin",
-            CARGO_PKG_NAME,
-        ];
-
-        // run the installer
-        let mut installer = std::process::Command::new(INSTALL_COMMAND[0]);
-        let exit_status = installer
-            .args(&INSTALL_COMMAND[1..])
-            .spawn()
-            .context("Failed to spawn installer process.")?
-            .wait()
-            .context("Failed to wait for installer process.")?;
-
-        if !exit_status.success() {
-            anyhow::bail!("Failed to install executable: {exit_status}");
-        }
+pub fn install(options: &Options) -> Result<Vec<FileChange>, InstallError> {
+    if let Some(package) = options.package {
+        return Err(InstallError::Usage(anyhow::anyhow!(
+            "Building a {package:?} package is only supported on macOS"
+        )));
     }
 
-    let exe_path = directories::UserDirs::new()
-        .context("Could not load home directory")?
-        .home_dir()
-        .join(".cargo")
-        .join("bin")
-        .join(CARGO_PKG_NAME)
-        .with_extension("exe");
-    let shortcut_path = get_shortcut_path(CARGO_PKG_NAME)?;
+    let previous = tracking::load_previous()?;
+    let install_dir = install_dir(options)?;
+    let built_exe = build_or_locate(options)?;
+    fs::create_dir_all(&install_dir).map_err(|source| InstallError::Destination {
+        path: install_dir.clone(),
+        source,
+    })?;
 
-    #[cfg(debug_assertions)]
-    println!("Installing shortcut with args: {AUMID:?} {exe_path:?} {shortcut_path:?}");
+    // the package name constant is modified in debug mode to include "-debug"
+    let exe_path = install_dir.join(CARGO_PKG_NAME).with_extension("exe");
+    let mut changes =
+        vec![copy_if_changed(&built_exe, &exe_path).map_err(|e| write_error(&exe_path, e))?];
+    let resource_dir = install_dir.join(RESOURCE_DIR_NAME);
+    changes.extend(copy_resources(&resource_dir).map_err(|e| write_error(&resource_dir, e))?);
+    let default_config =
+        write_default_config(&install_dir).map_err(|e| write_error(&install_dir, e))?;
+    let default_config_path = default_config.path.clone();
+    changes.push(default_config);
+    println!("Installed to {}", install_dir.display());
 
-    // check if the shortcut exists
-    if shortcut_path.exists() {
-        // if it does, delete it
-        eprintln!("Found existing shortcut, it will be overwritten");
-        std::fs::remove_file(&shortcut_path).context("Failed to delete existing shortcut.")?;
+    // the Start Menu shortcut carries the AUMID, which notifications need to show up as spotikill
+    let mut shortcuts = vec![get_start_menu_shortcut_path(CARGO_PKG_NAME)?];
+    if options.no_startup {
+        println!("Not starting at login, check \"Start at login\" in the tray menu to change that");
+    } else {
+        shortcuts.push(get_startup_shortcut_path(CARGO_PKG_NAME)?);
+    }
+    // everything the shortcuts need is checked before COM is touched
+    for shortcut in &shortcuts {
+        clear_shortcut_path(shortcut)?;
+        check_shortcut(&exe_path, shortcut)?;
+    }
+    unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }
+        .context("Failed to initialize COM")
+        .map_err(InstallError::System)?;
+    for shortcut in shortcuts {
+        install_shortcut(get_aumid(), &exe_path, &shortcut)?;
+        println!("Successfully installed shortcut to {}", shortcut.display());
+        changes.push(tracking::change_since(previous.as_ref(), &shortcut)?);
     }
 
-    unsafe {
-        CoInitializeEx(None, COINIT_APARTMENTTHREADED)?;
-        install_shortcut(AUMID, &exe_path, &shortcut_path)?;
-    };
+    uninstall::register(&exe_path, &install_dir).map_err(InstallError::System)?;
     println!(
-        "Successfully installed shortcut to {}",
-        shortcut_path.display()
+        r"Registered in Apps & features as HKCU\{}, uninstall from there or with `{CARGO_PKG_NAME} uninstall`",
+        uninstall::UNINSTALL_KEY
+    );
+
+    // a Startup shortcut from an install without --no-startup is removed here
+    let removed = tracking::record(&install_dir, &changes, previous.as_ref())?;
+    changes.extend(removed);
+    // the config belongs to the user, so it isn't recorded and uninstalling keeps it
+    changes.extend(
+        seed_user_config(&default_config_path)?
+            .map(|path| FileChange::new(path, ChangeKind::Created)),
     );
+    Ok(changes)
+}
 
+/// Makes way for a new shortcut: shortcuts can't be updated in place, so an existing one is
+/// deleted, and the folder is created if it is missing.
+fn clear_shortcut_path(shortcut_path: &Path) -> Result<(), InstallError> {
+    if shortcut_path.exists() {
+        eprintln!(
+            "Found existing shortcut at {}, it will be overwritten",
+            shortcut_path.display()
+        );
+        fs::remove_file(shortcut_path).map_err(|source| InstallError::Destination {
+            path: shortcut_path.to_path_buf(),
+            source,
+        })?;
+    }
+    if let Some(parent) = shortcut_path.parent() {
+        fs::create_dir_all(parent).map_err(|source| InstallError::Destination {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    }
     Ok(())
 }
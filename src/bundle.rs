@@ -0,0 +1,478 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::{constants::CARGO_PKG_NAME, resources::Resource};
+
+/// The layout of a macOS app bundle. Nothing here needs a Mac, so bundles can be assembled and
+/// checked on any host.
+///
+/// ```text
+/// spotikill.app/
+/// └── Contents/
+///     ├── Info.plist
+///     ├── MacOS/spotikill
+///     └── Resources/
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppBundle {
+    root: PathBuf,
+    executable_name: String,
+}
+
+impl AppBundle {
+    /// Describes the bundle at `<dir>/<name>.app` whose executable is called `name`. Nothing is
+    /// created on disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory the bundle is in.
+    /// * `name` - The name of the app, used for both the bundle and the executable.
+    pub fn new(dir: &Path, name: &str) -> Self {
+        Self {
+            root: dir.join(format!("{name}.app")),
+            executable_name: name.to_owned(),
+        }
+    }
+
+    /// The `.app` directory itself.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn contents_dir(&self) -> PathBuf {
+        self.root.join("Contents")
+    }
+
+    pub fn macos_dir(&self) -> PathBuf {
+        self.contents_dir().join("MacOS")
+    }
+
+    pub fn resources_dir(&self) -> PathBuf {
+        self.contents_dir().join("Resources")
+    }
+
+    pub fn executable(&self) -> PathBuf {
+        self.macos_dir().join(&self.executable_name)
+    }
+
+    pub fn info_plist(&self) -> PathBuf {
+        self.contents_dir().join("Info.plist")
+    }
+
+    /// Lists every file in the bundle, sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle can't be read.
+    pub fn files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+            for entry in fs::read_dir(dir)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    walk(&entry.path(), files)?;
+                } else {
+                    files.push(entry.path());
+                }
+            }
+            Ok(())
+        }
+
+        let mut files = Vec::new();
+        walk(&self.root, &mut files)
+            .with_context(|| format!("Failed to read {}", self.root.display()))?;
+        files.sort();
+        Ok(files)
+    }
+
+    /// Creates the empty directory structure, removing any previous bundle at the same path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the old bundle can't be removed or the directories can't be created.
+    pub fn create_skeleton(&self) -> anyhow::Result<()> {
+        remove_if_exists(&self.root)?;
+        for dir in [self.macos_dir(), self.resources_dir()] {
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Assembles the whole bundle: the directory layout, `Info.plist`, the executable and the
+    /// resources. Works on any host, only turning it into a DMG needs a Mac.
+    ///
+    /// # Arguments
+    ///
+    /// * `executable` - The compiled app, copied to `Contents/MacOS`.
+    /// * `info_plist` - Written to `Contents/Info.plist`. Its executable must be this bundle's.
+    /// * `resources` - Files written to `Contents/Resources`. Must include the icon the plist
+    ///   names.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plist is invalid or doesn't fit the bundle, a file is missing or
+    /// anything can't be written. Nothing is touched on disk if the inputs are invalid.
+    pub fn build(
+        &self,
+        executable: &Path,
+        info_plist: &InfoPlist,
+        resources: &[Resource],
+    ) -> anyhow::Result<()> {
+        info_plist.validate()?;
+        anyhow::ensure!(
+            info_plist.executable == self.executable_name,
+            "Info.plist names the executable {:?} but the bundle's is {:?}",
+            info_plist.executable,
+            self.executable_name
+        );
+        anyhow::ensure!(
+            executable.is_file(),
+            "Executable {} doesn't exist",
+            executable.display()
+        );
+        let has_icon = resources
+            .iter()
+            .any(|resource| resource.file_name == info_plist.icon_file);
+        anyhow::ensure!(
+            has_icon,
+            "The icon {} isn't among the resources",
+            info_plist.icon_file
+        );
+
+        self.create_skeleton()?;
+        let bundled_executable = self.executable();
+        fs::copy(executable, &bundled_executable).with_context(|| {
+            format!(
+                "Failed to copy {} to {}",
+                executable.display(),
+                bundled_executable.display()
+            )
+        })?;
+        info_plist.write(&self.info_plist())?;
+        let resources_dir = self.resources_dir();
+        for resource in resources {
+            let target = resources_dir.join(resource.file_name);
+            fs::write(&target, resource.contents)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Copies the bundle into `dest_dir`, e.g. `/Applications`, replacing any existing version.
+    ///
+    /// The new bundle is copied next to its final location first and only swapped in once it is
+    /// complete, so a failed install leaves the old version untouched. The swap itself is two
+    /// renames; if the second one fails, the old version is put back.
+    ///
+    /// # Returns
+    ///
+    /// The installed bundle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle can't be copied or swapped in.
+    pub fn install_into(&self, dest_dir: &Path) -> anyhow::Result<Self> {
+        let file_name = self
+            .root
+            .file_name()
+            .context("App bundle path has no file name")?
+            .to_string_lossy()
+            .into_owned();
+        let installed = Self {
+            root: dest_dir.join(&file_name),
+            executable_name: self.executable_name.clone(),
+        };
+        // dot-prefixed so Finder doesn't show them; same directory so renames don't cross devices
+        let staging = dest_dir.join(format!(".{file_name}.new"));
+        let backup = dest_dir.join(format!(".{file_name}.old"));
+
+        fs::create_dir_all(dest_dir)
+            .with_context(|| format!("Failed to create {}", dest_dir.display()))?;
+        remove_if_exists(&staging)?;
+        remove_if_exists(&backup)?;
+        if let Err(e) = copy_dir_all(&self.root, &staging) {
+            // don't leave a half-copied bundle lying around
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e).with_context(|| {
+                format!(
+                    "Failed to copy {} to {}",
+                    self.root.display(),
+                    staging.display()
+                )
+            });
+        }
+
+        let had_old = installed.root.exists();
+        if had_old {
+            fs::rename(&installed.root, &backup).with_context(|| {
+                format!("Failed to move {} out of the way", installed.root.display())
+            })?;
+        }
+        if let Err(e) = fs::rename(&staging, &installed.root) {
+            if had_old {
+                let _ = fs::rename(&backup, &installed.root);
+            }
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e).with_context(|| {
+                format!(
+                    "Failed to move the new bundle to {}",
+                    installed.root.display()
+                )
+            });
+        }
+        if had_old {
+            fs::remove_dir_all(&backup).with_context(|| {
+                format!("Failed to remove the old bundle at {}", backup.display())
+            })?;
+        }
+        Ok(installed)
+    }
+}
+
+/// Removes a file or directory tree, doing nothing if it doesn't exist.
+fn remove_if_exists(path: &Path) -> anyhow::Result<()> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Recursively copies the directory `src` to `dest`. File permissions are kept, so executables
+/// stay executable.
+fn copy_dir_all(src: &Path, dest: &Path) -> io::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Extra `Info.plist` settings, read from `[package.metadata.bundle]` in `Cargo.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BundleConfig {
+    /// `LSMinimumSystemVersion`, e.g. `"10.15"`.
+    pub minimum_system_version: Option<String>,
+    /// `NSHumanReadableCopyright`.
+    pub copyright: Option<String>,
+    /// `LSUIElement`, which hides the app from the dock. Defaults to hiding it in release builds
+    /// only.
+    pub ui_element: Option<bool>,
+}
+
+impl BundleConfig {
+    /// Reads the bundle settings from the contents of a `Cargo.toml`. A manifest without a
+    /// `[package.metadata.bundle]` table gets the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest isn't valid TOML or the table has unknown or mistyped keys.
+    pub fn from_manifest(manifest: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Manifest {
+            package: Package,
+        }
+        #[derive(Deserialize)]
+        struct Package {
+            #[serde(default)]
+            metadata: Metadata,
+        }
+        #[derive(Default, Deserialize)]
+        struct Metadata {
+            #[serde(default)]
+            bundle: BundleConfig,
+        }
+
+        let manifest: Manifest = toml::from_str(manifest).context("Failed to parse Cargo.toml")?;
+        Ok(manifest.package.metadata.bundle)
+    }
+}
+
+/// The `Info.plist` of the app bundle. Fields are written in declaration order.
+///
+/// SEE: https://developer.apple.com/documentation/bundleresources/information_property_list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InfoPlist {
+    #[serde(rename = "CFBundleIdentifier")]
+    pub identifier: String,
+    #[serde(rename = "CFBundleName")]
+    pub name: String,
+    #[serde(rename = "CFBundleExecutable")]
+    pub executable: String,
+    #[serde(rename = "CFBundleIconFile")]
+    pub icon_file: String,
+    #[serde(rename = "CFBundleIconName")]
+    pub icon_name: String,
+    #[serde(rename = "CFBundleVersion")]
+    pub version: String,
+    #[serde(rename = "CFBundleShortVersionString")]
+    pub short_version: String,
+    #[serde(rename = "CFBundlePackageType")]
+    pub package_type: String,
+    #[serde(rename = "CFBundleInfoDictionaryVersion")]
+    pub info_dictionary_version: String,
+    #[serde(rename = "CFBundleDevelopmentRegion")]
+    pub development_region: String,
+    #[serde(rename = "CFBundleSupportedPlatforms")]
+    pub supported_platforms: Vec<String>,
+    #[serde(
+        rename = "LSMinimumSystemVersion",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub minimum_system_version: Option<String>,
+    #[serde(
+        rename = "NSHumanReadableCopyright",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub copyright: Option<String>,
+    #[serde(rename = "LSUIElement")]
+    pub ui_element: bool,
+    #[serde(rename = "NSHighResolutionCapable")]
+    pub high_resolution_capable: bool,
+    #[serde(rename = "NSMainNibFile")]
+    pub main_nib_file: String,
+}
+
+impl InfoPlist {
+    /// Describes an app called `name` whose executable has the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the app. Characters that aren't allowed in a bundle identifier are
+    ///   replaced with `-` there.
+    /// * `version` - The app version. Bundle versions are only numbers, so pre-release and build
+    ///   metadata are dropped.
+    /// * `config` - Extra settings.
+    pub fn new(name: &str, version: &Version, config: &BundleConfig) -> Self {
+        let identifier_name: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let version = format!("{}.{}.{}", version.major, version.minor, version.patch);
+        Self {
+            identifier: format!("com.dablenparty.{identifier_name}"),
+            name: name.to_owned(),
+            executable: name.to_owned(),
+            icon_file: "app-icon.icns".to_owned(),
+            icon_name: "app-icon".to_owned(),
+            short_version: version.clone(),
+            version,
+            package_type: "APPL".to_owned(),
+            info_dictionary_version: "6.0".to_owned(),
+            development_region: "en".to_owned(),
+            supported_platforms: vec!["MacOSX".to_owned()],
+            minimum_system_version: config.minimum_system_version.clone(),
+            copyright: config.copyright.clone(),
+            ui_element: config.ui_element.unwrap_or(!cfg!(debug_assertions)),
+            high_resolution_capable: true,
+            main_nib_file: String::new(),
+        }
+    }
+
+    /// Describes this app.
+    pub fn for_spotikill(config: &BundleConfig) -> Self {
+        Self::new(CARGO_PKG_NAME, &crate::update::current_version(), config)
+    }
+
+    /// Checks the values macOS is picky about.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first invalid value.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let identifier_is_valid = !self.identifier.is_empty()
+            && self
+                .identifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+        anyhow::ensure!(
+            identifier_is_valid,
+            "Invalid bundle identifier {:?}: only letters, digits, '-' and '.' are allowed",
+            self.identifier
+        );
+        anyhow::ensure!(!self.name.is_empty(), "The bundle name is empty");
+        let executable_is_valid = !self.executable.is_empty()
+            && !matches!(self.executable.as_str(), "." | "..")
+            && !self.executable.contains(['/', '\0']);
+        anyhow::ensure!(
+            executable_is_valid,
+            "Invalid executable name {:?}: it must be a file name",
+            self.executable
+        );
+        for (key, value) in [
+            ("CFBundleVersion", &self.version),
+            ("CFBundleShortVersionString", &self.short_version),
+        ] {
+            anyhow::ensure!(
+                is_bundle_version(value),
+                "Invalid {key} {value:?}: it must be up to three numbers separated by '.'"
+            );
+        }
+        if let Some(version) = &self.minimum_system_version {
+            anyhow::ensure!(
+                is_bundle_version(version),
+                "Invalid LSMinimumSystemVersion {version:?}: it must be like \"10.15\""
+            );
+        }
+        Ok(())
+    }
+
+    /// Validates and serializes this as an XML property list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it isn't valid.
+    pub fn to_xml(&self) -> anyhow::Result<Vec<u8>> {
+        self.validate()?;
+        let mut xml = Vec::new();
+        plist::to_writer_xml(&mut xml, self).context("Failed to serialize Info.plist")?;
+        // end the file with a newline like every other text file
+        xml.push(b'\n');
+        Ok(xml)
+    }
+
+    /// Validates and writes this to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it isn't valid or can't be written.
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let xml = self.to_xml()?;
+        fs::write(path, xml).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Checks for one to three non-negative integers separated by periods, e.g. `1`, `10.15` or
+/// `0.3.0`.
+fn is_bundle_version(version: &str) -> bool {
+    let parts: Vec<_> = version.split('.').collect();
+    parts.len() <= 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
@@ -0,0 +1,270 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use directories::BaseDirs;
+
+use crate::{
+    flavour::{InstallFlavour, FLATPAK_APP_ID, SNAP_NAME},
+    matcher::ProcessMatcher,
+    process::{find_spotify_pids, format_bytes},
+};
+
+/// Spotify keeps the index of offline downloads next to the downloaded files, so a directory
+/// containing this file holds offline downloads. The index names the files it refers to by their
+/// ID, either spelled out in hex or as raw bytes.
+const OFFLINE_INDEX_FILE_NAME: &str = "offline.bnk";
+
+/// Gets the directory a flavour of Spotify uses as its cache on this platform. It may not exist.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn spotify_cache_dir(flavour: InstallFlavour) -> anyhow::Result<PathBuf> {
+    let base_dirs = BaseDirs::new().context("Failed to get home directory")?;
+    let home_dir = base_dirs.home_dir();
+    // sandboxed Spotify gets a home of its own, whatever XDG_CACHE_HOME says outside of it
+    let cache_dir = match flavour {
+        InstallFlavour::Flatpak => home_dir
+            .join(".var")
+            .join("app")
+            .join(FLATPAK_APP_ID)
+            .join("cache")
+            .join("spotify"),
+        InstallFlavour::Snap => home_dir
+            .join("snap")
+            .join(SNAP_NAME)
+            .join("common")
+            .join(".cache")
+            .join("spotify"),
+        InstallFlavour::Native | InstallFlavour::Deb if cfg!(windows) => {
+            base_dirs.data_local_dir().join("Spotify").join("Storage")
+        }
+        InstallFlavour::Native | InstallFlavour::Deb if cfg!(target_os = "macos") => {
+            base_dirs.cache_dir().join("com.spotify.client")
+        }
+        InstallFlavour::Native | InstallFlavour::Deb => base_dirs.cache_dir().join("spotify"),
+    };
+    Ok(cache_dir)
+}
+
+/// Gets the directories every flavour of Spotify on this platform uses as its cache. They may not
+/// exist.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn spotify_cache_dirs() -> anyhow::Result<Vec<PathBuf>> {
+    // the deb package shares the native cache directory
+    let flavours: &[_] = if cfg!(target_os = "linux") {
+        &[
+            InstallFlavour::Native,
+            InstallFlavour::Flatpak,
+            InstallFlavour::Snap,
+        ]
+    } else {
+        &[InstallFlavour::Native]
+    };
+    flavours
+        .iter()
+        .map(|&flavour| spotify_cache_dir(flavour))
+        .collect()
+}
+
+/// What happened during a single call to [`clear_spotify_cache`].
+#[derive(Debug, Clone, Default)]
+pub struct CacheReport {
+    /// Total size (in bytes) of the cache before anything was deleted.
+    pub size_before: u64,
+    /// Size (in bytes) of everything that was deleted.
+    pub deleted: u64,
+    /// Directories holding offline downloads, in which only the index and the files it refers to
+    /// were kept.
+    pub kept_offline: Vec<PathBuf>,
+}
+
+impl CacheReport {
+    /// A one-line summary, e.g. "Cleared 1.1 GB of 1.4 GB, kept offline downloads".
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Cleared {} of {}",
+            format_bytes(self.deleted),
+            format_bytes(self.size_before)
+        );
+        if !self.kept_offline.is_empty() {
+            summary.push_str(", kept offline downloads");
+        }
+        summary
+    }
+}
+
+/// Gets the total size of a file or directory without following symlinks.
+fn disk_usage(path: &Path) -> io::Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    fs::read_dir(path)?.try_fold(0, |total, entry| Ok(total + disk_usage(&entry?.path())?))
+}
+
+/// Checks whether a directory or any of its subdirectories holds offline downloads.
+fn holds_offline_downloads(dir: &Path) -> io::Result<bool> {
+    if dir.join(OFFLINE_INDEX_FILE_NAME).exists() {
+        return Ok(true);
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() && holds_offline_downloads(&entry.path())? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Checks whether the offline index refers to a file, by the ID its name starts with.
+fn is_referenced(index: &[u8], path: &Path) -> bool {
+    let Some(id) = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|id| !id.is_empty())
+    else {
+        return false;
+    };
+    let contains = |needle: &[u8]| index.windows(needle.len()).any(|window| window == needle);
+    if contains(id.as_bytes()) {
+        return true;
+    }
+
+    let is_hex = id.len() % 2 == 0 && id.bytes().all(|b| b.is_ascii_hexdigit());
+    is_hex && {
+        let raw: Vec<_> = (0..id.len())
+            .step_by(2)
+            .filter_map(|i| u8::from_str_radix(&id[i..i + 2], 16).ok())
+            .collect();
+        contains(&raw)
+    }
+}
+
+/// Deletes everything inside a directory holding offline downloads except for the offline index
+/// and the files it refers to. Subdirectories are only deleted if nothing in them is kept.
+///
+/// # Returns
+///
+/// Whether anything was kept.
+fn clear_offline_dir(dir: &Path, index: &[u8], report: &mut CacheReport) -> anyhow::Result<bool> {
+    let mut kept = false;
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let is_dir = fs::symlink_metadata(&path)?.is_dir();
+        if is_dir && clear_offline_dir(&path, index, report)? {
+            kept = true;
+            continue;
+        }
+        if !is_dir
+            && (path.file_name() == Some(OFFLINE_INDEX_FILE_NAME.as_ref())
+                || is_referenced(index, &path))
+        {
+            kept = true;
+            continue;
+        }
+
+        let size = disk_usage(&path)?;
+        if is_dir {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        }
+        .with_context(|| format!("Failed to delete {}", path.display()))?;
+        report.deleted += size;
+    }
+    Ok(kept)
+}
+
+/// Deletes everything inside a directory, except for offline downloads unless `include_offline`
+/// is set.
+fn clear_dir(dir: &Path, include_offline: bool, report: &mut CacheReport) -> anyhow::Result<()> {
+    let index_path = dir.join(OFFLINE_INDEX_FILE_NAME);
+    if !include_offline && index_path.exists() {
+        let index = fs::read(&index_path)
+            .with_context(|| format!("Failed to read {}", index_path.display()))?;
+        report.kept_offline.push(dir.to_path_buf());
+        clear_offline_dir(dir, &index, report)?;
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let is_dir = fs::symlink_metadata(&path)?.is_dir();
+        if is_dir && !include_offline && holds_offline_downloads(&path)? {
+            // only spare the directories that actually hold the downloads
+            clear_dir(&path, include_offline, report)?;
+            continue;
+        }
+
+        let size = disk_usage(&path)?;
+        if is_dir {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        }
+        .with_context(|| format!("Failed to delete {}", path.display()))?;
+        report.deleted += size;
+    }
+    Ok(())
+}
+
+/// Deletes Spotify's cache. Only the known cache directories are touched.
+///
+/// # Arguments
+///
+/// * `include_offline` - Whether to delete offline downloads as well.
+///
+/// # Errors
+///
+/// Returns an error if Spotify is running or if anything fails to be deleted.
+pub fn clear_spotify_cache(include_offline: bool) -> anyhow::Result<CacheReport> {
+    // The user's exclude list doesn't apply here since excluded processes may still be using the
+    // cache.
+    let spotify_running = !find_spotify_pids(&ProcessMatcher::default()).is_empty();
+    clear_cache(&spotify_cache_dirs()?, include_offline, spotify_running)
+}
+
+/// Deletes everything inside the given cache directories. Directories that don't exist are
+/// skipped.
+///
+/// # Arguments
+///
+/// * `dirs` - The cache directories.
+/// * `include_offline` - Whether to delete offline downloads as well.
+/// * `spotify_running` - Whether Spotify is running, in which case nothing is deleted.
+///
+/// # Errors
+///
+/// Returns an error if Spotify is running or if anything fails to be deleted.
+pub fn clear_cache<P: AsRef<Path>>(
+    dirs: &[P],
+    include_offline: bool,
+    spotify_running: bool,
+) -> anyhow::Result<CacheReport> {
+    // deleting the cache from under a running Spotify can corrupt it
+    anyhow::ensure!(
+        !spotify_running,
+        "Spotify is still running, refusing to clear its cache"
+    );
+
+    let mut report = CacheReport::default();
+    for dir in dirs.iter().map(AsRef::as_ref) {
+        if !dir.is_dir() {
+            continue;
+        }
+
+        #[cfg(debug_assertions)]
+        println!("Clearing cache at {}", dir.display());
+
+        report.size_before += disk_usage(dir)?;
+        clear_dir(dir, include_offline, &mut report)?;
+    }
+    Ok(report)
+}
@@ -0,0 +1,121 @@
+use std::{fs, io, path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    confirm::ConfirmPolicy, constants::CARGO_PKG_NAME, restore::AfterRestart, role::ProcessRole,
+    schedule::ScheduleConfig,
+};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Gets the platform-specific project directories for this app.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn project_dirs() -> anyhow::Result<ProjectDirs> {
+    ProjectDirs::from("com", "dablenparty", CARGO_PKG_NAME)
+        .context("Failed to determine the project directories")
+}
+
+/// User configuration, loaded from `config.toml` in the config directory. Every field is
+/// optional in the file and falls back to its default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// How often (in seconds) Spotify's status is refreshed in the tray.
+    pub status_refresh_secs: u64,
+    /// Total Spotify memory usage (in MB) above which the tray icon shows a warning badge.
+    pub memory_warning_mb: u64,
+    /// Total Spotify CPU usage (in percent) above which the tray icon shows a warning badge.
+    pub cpu_warning_percent: u32,
+    /// How long (in minutes) without keyboard or mouse input counts as idle.
+    pub idle_minutes: u64,
+    /// Process names or full executable paths that are never treated as Spotify.
+    pub exclude_processes: Vec<String>,
+    /// Whether Spotify processes owned by other users may be killed.
+    pub kill_other_users: bool,
+    /// Whether Spotify processes running as root or elevated may be killed.
+    pub kill_elevated: bool,
+    /// Which kinds of Spotify processes a kill includes. The rest are left running.
+    pub kill_roles: Vec<ProcessRole>,
+    /// When to ask before killing Spotify from the tray menu or the command line.
+    pub confirm_kill: ConfirmPolicy,
+    /// Whether "Kill and clear cache" deletes offline downloads too.
+    pub clear_offline_downloads: bool,
+    /// Where to look for new releases: an `http(s)://` or `file://` URL, or a local path to a JSON
+    /// release manifest. Update checks are off if this isn't set.
+    pub update_feed: Option<String>,
+    /// How often (in hours) to check the update feed.
+    pub update_check_hours: u64,
+    /// What to put back after restarting Spotify, written as an `[after_restart]` table.
+    pub after_restart: AfterRestart,
+    /// Scheduled kills and restarts, written as `[[schedule]]` tables.
+    pub schedule: Vec<ScheduleConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            status_refresh_secs: 5,
+            memory_warning_mb: 2048,
+            cpu_warning_percent: 80,
+            idle_minutes: 10,
+            exclude_processes: Vec::new(),
+            kill_other_users: false,
+            kill_elevated: false,
+            kill_roles: ProcessRole::ALL.to_vec(),
+            confirm_kill: ConfirmPolicy::Never,
+            clear_offline_downloads: false,
+            update_feed: None,
+            update_check_hours: 24,
+            after_restart: AfterRestart::default(),
+            schedule: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Gets the path to the config file. The file may not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config directory cannot be determined.
+    pub fn path() -> anyhow::Result<PathBuf> {
+        Ok(project_dirs()?.config_dir().join(CONFIG_FILE_NAME))
+    }
+
+    /// Loads the config file, or the default config if there isn't one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file exists but cannot be read or parsed.
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path()?;
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// The status refresh interval. Clamped to at least one second so the tray never busy-polls,
+    /// and to at most a day so the next refresh can always be scheduled.
+    pub fn status_refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.status_refresh_secs.clamp(1, 24 * 60 * 60))
+    }
+
+    /// How often to check the update feed. Clamped to at least an hour to go easy on the feed.
+    pub fn update_check_interval(&self) -> Duration {
+        Duration::from_secs(self.update_check_hours.max(1).saturating_mul(60 * 60))
+    }
+
+    /// How long the user must be inactive to count as idle.
+    pub fn idle_threshold(&self) -> Duration {
+        Duration::from_secs(self.idle_minutes.saturating_mul(60))
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    matcher::ProcessMatcher,
+    playback::{PlaybackDetector, PlaybackState},
+    process::preview_kill,
+    safety::KillPolicy,
+};
+
+/// When to ask before killing Spotify.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmPolicy {
+    Always,
+    #[default]
+    Never,
+    /// Only while Spotify says it's playing. If that can't be told, it doesn't ask.
+    WhenPlaying,
+}
+
+impl ConfirmPolicy {
+    /// Checks whether to ask before killing Spotify.
+    ///
+    /// # Arguments
+    ///
+    /// * `playback` - Tells whether Spotify is playing. Only asked for
+    ///   [`ConfirmPolicy::WhenPlaying`].
+    pub fn must_ask<P: PlaybackDetector>(self, playback: &P) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::WhenPlaying => playback.playback_state() == PlaybackState::Playing,
+        }
+    }
+}
+
+/// Asks the user whether to go ahead with a kill, if the policy says to.
+///
+/// # Arguments
+///
+/// * `matcher` - Decides which processes belong to Spotify.
+/// * `policy` - Decides which of those may be killed.
+/// * `confirm` - When to ask.
+/// * `playback` - Tells whether Spotify is playing, e.g. [`crate::playback::SystemPlayback`].
+/// * `ask` - Asks the user a yes/no question, e.g. [`ask_with_dialog`].
+///
+/// # Returns
+///
+/// Whether to go ahead. If there is nothing that may be killed, this doesn't ask and returns
+/// `true` so the kill itself can report why.
+///
+/// # Errors
+///
+/// Returns an error if `ask` fails.
+pub fn confirm_kill<P, F>(
+    matcher: &ProcessMatcher,
+    policy: KillPolicy,
+    confirm: ConfirmPolicy,
+    playback: &P,
+    ask: F,
+) -> anyhow::Result<bool>
+where
+    P: PlaybackDetector,
+    F: FnOnce(&str) -> anyhow::Result<bool>,
+{
+    if !confirm.must_ask(playback) {
+        return Ok(true);
+    }
+
+    let preview = preview_kill(matcher, policy);
+    if preview.targets.is_empty() {
+        return Ok(true);
+    }
+    if confirm == ConfirmPolicy::WhenPlaying {
+        // the music stops too, which is easy to forget when the UI is frozen
+        return ask(&format!("Spotify is still playing. {}", preview.question()));
+    }
+    ask(&preview.question())
+}
+
+/// Asks a yes/no question with a native dialog. Returns `true` if the user said yes.
+///
+/// # Errors
+///
+/// Returns an error if the dialog can't be shown.
+#[cfg(windows)]
+pub fn ask_with_dialog(question: &str) -> anyhow::Result<bool> {
+    use windows::core::HSTRING;
+    use windows::Win32::{
+        Foundation::HWND,
+        UI::WindowsAndMessaging::{
+            MessageBoxW, IDYES, MB_ICONWARNING, MB_SETFOREGROUND, MB_TOPMOST, MB_YESNO,
+        },
+    };
+
+    let result = unsafe {
+        MessageBoxW(
+            HWND::default(),
+            &HSTRING::from(question),
+            &HSTRING::from(crate::constants::CARGO_PKG_NAME),
+            MB_YESNO | MB_ICONWARNING | MB_SETFOREGROUND | MB_TOPMOST,
+        )
+    };
+    anyhow::ensure!(result.0 != 0, windows::core::Error::from_win32());
+    Ok(result == IDYES)
+}
+
+/// Asks a yes/no question with a native dialog. Returns `true` if the user said yes.
+///
+/// # Errors
+///
+/// Returns an error if the dialog can't be shown.
+#[cfg(target_os = "macos")]
+pub fn ask_with_dialog(question: &str) -> anyhow::Result<bool> {
+    use anyhow::Context;
+
+    // AppleScript string literals only need quotes and backslashes escaped
+    let question = question.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        r#"display dialog "{question}" with title "{}" buttons {{"Cancel", "Kill"}} default button "Kill" cancel button "Cancel" with icon caution"#,
+        crate::constants::CARGO_PKG_NAME
+    );
+    let output = std::process::Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .context("Failed to run osascript")?;
+    // pressing "Cancel" makes osascript fail with error -128
+    let cancelled = String::from_utf8_lossy(&output.stderr).contains("-128");
+    anyhow::ensure!(
+        output.status.success() || cancelled,
+        "Failed to show dialog: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(output.status.success())
+}
+
+/// Asks a yes/no question with a native dialog. Returns `true` if the user said yes.
+///
+/// Uses `zenity` or, failing that, `kdialog`.
+///
+/// # Errors
+///
+/// Returns an error if neither is installed.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn ask_with_dialog(question: &str) -> anyhow::Result<bool> {
+    use std::{io, process::Command};
+
+    use anyhow::Context;
+
+    use crate::constants::CARGO_PKG_NAME;
+
+    let mut zenity = Command::new("zenity");
+    zenity.args(["--question", "--title", CARGO_PKG_NAME, "--text", question]);
+    let mut kdialog = Command::new("kdialog");
+    kdialog.args(["--title", CARGO_PKG_NAME, "--yesno", question]);
+
+    for mut command in [zenity, kdialog] {
+        match command.status() {
+            // both exit with 0 for yes and 1 for no
+            Ok(status) => return Ok(status.success()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to run {command:?}")),
+        }
+    }
+    anyhow::bail!(
+        "Can't ask for confirmation: install zenity or kdialog, or set confirm_kill = \"never\""
+    )
+}
@@ -1,26 +1,13 @@
-use const_format::concatcp;
-
 #[cfg(feature = "installer")]
 pub const CARGO_BINARY: &str = env!("CARGO");
 pub const CARGO_MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+// set by build.rs to the icons it generated
+pub const BUILD_RESOURCE_DIR: &str = env!("SPOTIKILL_RESOURCE_DIR");
 #[cfg(debug_assertions)]
-pub const CARGO_PKG_NAME: &str = concatcp!(env!("CARGO_PKG_NAME"), "-debug");
+pub const CARGO_PKG_NAME: &str = const_format::concatcp!(env!("CARGO_PKG_NAME"), "-debug");
 #[cfg(not(debug_assertions))]
 pub const CARGO_PKG_NAME: &str = env!("CARGO_PKG_NAME");
 #[cfg(debug_assertions)]
-pub const CARGO_PKG_VERSION: &str = concatcp!(env!("CARGO_PKG_VERSION"), "-debug");
+pub const CARGO_PKG_VERSION: &str = const_format::concatcp!(env!("CARGO_PKG_VERSION"), "-debug");
 #[cfg(not(debug_assertions))]
 pub const CARGO_PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
-#[cfg(windows)]
-const ICON_EXT: &str = "ico";
-#[cfg(unix)]
-const ICON_EXT: &str = "png";
-
-pub const ICON_PATH: &str = concatcp!(
-    env!("CARGO_MANIFEST_DIR"),
-    std::path::MAIN_SEPARATOR,
-    "resources",
-    std::path::MAIN_SEPARATOR,
-    "app-icon.",
-    ICON_EXT
-);
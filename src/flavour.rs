@@ -0,0 +1,125 @@
+use std::{fmt, path::Path, process::Command};
+
+use sysinfo::Process;
+
+/// The Flatpak application ID of the official Spotify client.
+pub const FLATPAK_APP_ID: &str = "com.spotify.Client";
+/// The name of the official Spotify Snap.
+pub const SNAP_NAME: &str = "spotify";
+
+/// How Spotify was installed. This decides how it is killed and relaunched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum InstallFlavour {
+    /// Anything not listed below, e.g. the Windows and macOS installers or a tarball on Linux.
+    #[default]
+    Native,
+    /// Flathub's `com.spotify.Client`, sandboxed by `bwrap`.
+    Flatpak,
+    /// The Snap Store's `spotify`, confined by `snap-confine`.
+    Snap,
+    /// The official `spotify-client` Debian package.
+    Deb,
+}
+
+impl fmt::Display for InstallFlavour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Native => "native",
+            Self::Flatpak => "Flatpak",
+            Self::Snap => "Snap",
+            Self::Deb => "deb",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl InstallFlavour {
+    /// Detects the flavour from what is known about a process.
+    ///
+    /// # Arguments
+    ///
+    /// * `exe` - The path to the process's executable, as seen from outside any sandbox.
+    /// * `cmd` - The process's command line.
+    /// * `cgroup` - The contents of `/proc/<pid>/cgroup`, empty if unavailable.
+    pub fn detect(exe: Option<&Path>, cmd: &[String], cgroup: &str) -> Self {
+        // flatpak and snap both put every app into its own systemd scope, e.g.
+        // `app-flatpak-com.spotify.Client-1234.scope` or `snap.spotify.spotify-<uuid>.scope`
+        let flatpak_scope = format!("app-flatpak-{FLATPAK_APP_ID}-");
+        let snap_scope = format!("snap.{SNAP_NAME}.");
+        let in_cmd = |needle: &str| cmd.iter().any(|arg| arg.contains(needle));
+        let exe_starts_with = |prefix: &str| exe.is_some_and(|exe| exe.starts_with(prefix));
+
+        if cgroup.contains(&flatpak_scope) || in_cmd(FLATPAK_APP_ID) || exe_starts_with("/app/") {
+            Self::Flatpak
+        } else if cgroup.contains(&snap_scope) || exe_starts_with("/snap/spotify/") {
+            Self::Snap
+        } else if exe_starts_with("/usr/share/spotify/") {
+            Self::Deb
+        } else {
+            Self::Native
+        }
+    }
+
+    /// Detects the flavour of a running process. Always [`InstallFlavour::Native`] outside of
+    /// Linux.
+    pub fn of_process(proc: &Process) -> Self {
+        if !cfg!(target_os = "linux") {
+            return Self::Native;
+        }
+        let cgroup_path = format!("/proc/{}/cgroup", proc.pid());
+        let cgroup = std::fs::read_to_string(cgroup_path).unwrap_or_default();
+        Self::detect(proc.exe(), proc.cmd(), &cgroup)
+    }
+
+    /// Gets the command that kills every Spotify process of this flavour at once, if there is
+    /// one. Otherwise, processes must be killed one by one.
+    pub fn kill_command(self) -> Option<Command> {
+        match self {
+            Self::Flatpak => {
+                let mut command = Command::new("flatpak");
+                command.args(["kill", FLATPAK_APP_ID]);
+                Some(command)
+            }
+            Self::Native | Self::Snap | Self::Deb => None,
+        }
+    }
+
+    /// Gets the command that launches Spotify.
+    ///
+    /// # Arguments
+    ///
+    /// * `exe` - The executable of the previous main Spotify process, if known.
+    pub fn launch_command(self, exe: Option<&Path>) -> Command {
+        match (self, exe) {
+            (Self::Flatpak, _) => {
+                let mut command = Command::new("flatpak");
+                command.args(["run", FLATPAK_APP_ID]);
+                command
+            }
+            (Self::Snap, _) => {
+                let mut command = Command::new("snap");
+                command.args(["run", SNAP_NAME]);
+                command
+            }
+            // the package's launcher script sets up the environment before running the binary
+            (Self::Deb, _) => Command::new("spotify"),
+            (Self::Native, Some(exe)) => Command::new(exe),
+            (Self::Native, None) => default_launch_command(),
+        }
+    }
+}
+
+/// Launches Spotify the way the platform normally would.
+fn default_launch_command() -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("explorer.exe");
+        command.arg("spotify:");
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.args(["-a", "Spotify"]);
+        command
+    } else {
+        Command::new("spotify")
+    }
+}
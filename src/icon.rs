@@ -0,0 +1,123 @@
+use image::{Rgba, RgbaImage};
+
+use crate::process::SpotifyHealth;
+
+/// Colour of the badge drawn over the icon when Spotify is unhealthy.
+const BADGE_COLOR: Rgba<u8> = Rgba([229, 57, 53, 255]);
+/// Colour of the ring around the badge, so it stands out against the icon.
+const BADGE_RING_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+/// Colour of the pause badge drawn over the icon while snoozed.
+const PAUSE_BADGE_COLOR: Rgba<u8> = Rgba([84, 110, 122, 255]);
+
+/// Every variant of the tray icon, generated at runtime from a single base image.
+#[derive(Debug, Clone)]
+pub struct IconSet {
+    normal: RgbaImage,
+    badged: RgbaImage,
+    greyed: RgbaImage,
+    /// The same three, with a pause badge.
+    snoozed: [RgbaImage; 3],
+}
+
+impl IconSet {
+    pub fn new(base: RgbaImage) -> Self {
+        let badged = with_badge(&base);
+        let greyed = greyed_out(&base);
+        Self {
+            snoozed: [&base, &badged, &greyed].map(with_pause_badge),
+            badged,
+            greyed,
+            normal: base,
+        }
+    }
+
+    /// Gets the icon variant that represents the given health.
+    pub fn for_health(&self, health: SpotifyHealth) -> &RgbaImage {
+        match health {
+            SpotifyHealth::Healthy => &self.normal,
+            SpotifyHealth::Unhealthy => &self.badged,
+            SpotifyHealth::NotRunning => &self.greyed,
+        }
+    }
+
+    /// Gets the icon variant that represents the given health, marked as paused if snoozed.
+    pub fn for_state(&self, health: SpotifyHealth, snoozed: bool) -> &RgbaImage {
+        if !snoozed {
+            return self.for_health(health);
+        }
+        match health {
+            SpotifyHealth::Healthy => &self.snoozed[0],
+            SpotifyHealth::Unhealthy => &self.snoozed[1],
+            SpotifyHealth::NotRunning => &self.snoozed[2],
+        }
+    }
+}
+
+/// Converts the image to greyscale and halves its opacity.
+pub fn greyed_out(base: &RgbaImage) -> RgbaImage {
+    let mut image = base.clone();
+    for Rgba([r, g, b, a]) in image.pixels_mut() {
+        // ITU-R BT.601 luma
+        let luma = (u32::from(*r) * 299 + u32::from(*g) * 587 + u32::from(*b) * 114) / 1000;
+        let luma = u8::try_from(luma).unwrap_or(u8::MAX);
+        *r = luma;
+        *g = luma;
+        *b = luma;
+        *a /= 2;
+    }
+    image
+}
+
+/// Draws a round badge in the bottom-right corner of the image.
+pub fn with_badge(base: &RgbaImage) -> RgbaImage {
+    let mut image = base.clone();
+    let (width, height) = image.dimensions();
+    let size = width.min(height);
+    // the badge takes up a little under half of the icon
+    let radius = f64::from(size) * 0.22;
+    let ring = (f64::from(size) * 0.04).max(1.0);
+    let center_x = f64::from(width) - radius - ring;
+    let center_y = f64::from(height) - radius - ring;
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let dx = f64::from(x) + 0.5 - center_x;
+        let dy = f64::from(y) + 0.5 - center_y;
+        let distance = dx.hypot(dy);
+        if distance <= radius {
+            *pixel = BADGE_COLOR;
+        } else if distance <= radius + ring {
+            *pixel = BADGE_RING_COLOR;
+        }
+    }
+    image
+}
+
+/// Draws a round pause badge in the top-left corner of the image, opposite the health badge.
+pub fn with_pause_badge(base: &RgbaImage) -> RgbaImage {
+    let mut image = base.clone();
+    let size = image.width().min(image.height());
+    let radius = f64::from(size) * 0.22;
+    let ring = (f64::from(size) * 0.04).max(1.0);
+    let center = radius + ring;
+    // two bars, each a fifth of the badge wide with a fifth between them
+    let bar_width = radius * 0.4;
+    let bar_height = radius;
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let dx = f64::from(x) + 0.5 - center;
+        let dy = f64::from(y) + 0.5 - center;
+        let distance = dx.hypot(dy);
+        let in_bar =
+            dy.abs() <= bar_height / 2.0 && (bar_width / 2.0..=bar_width * 1.5).contains(&dx.abs());
+        if distance <= radius {
+            *pixel = if in_bar {
+                BADGE_RING_COLOR
+            } else {
+                PAUSE_BADGE_COLOR
+            };
+        } else if distance <= radius + ring {
+            *pixel = BADGE_RING_COLOR;
+        }
+    }
+    image
+}
@@ -0,0 +1,180 @@
+// build.rs includes this file directly, so it must not use anything from the crate
+
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use image::{imageops::FilterType, ImageOutputFormat, RgbaImage};
+
+/// The sizes in the `.ico`, as Windows uses them for the tray, taskbar, Explorer and Start.
+pub const ICO_SIZES: [u32; 7] = [16, 24, 32, 48, 64, 128, 256];
+
+/// The entries in the `.icns` with their pixel sizes, i.e. `@2x` entries count double. All of
+/// them hold PNG data.
+///
+/// SEE: https://en.wikipedia.org/wiki/Apple_Icon_Image_format#Icon_types
+pub const ICNS_ENTRIES: [([u8; 4], u32); 10] = [
+    (*b"icp4", 16),
+    (*b"icp5", 32),
+    (*b"ic11", 32),
+    (*b"ic12", 64),
+    (*b"ic07", 128),
+    (*b"ic13", 256),
+    (*b"ic08", 256),
+    (*b"ic14", 512),
+    (*b"ic09", 512),
+    (*b"ic10", 1024),
+];
+
+/// Paths to the generated icons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedIcons {
+    pub png: PathBuf,
+    pub ico: PathBuf,
+    pub icns: PathBuf,
+}
+
+/// Writes `app-icon.png`, `app-icon.ico` and `app-icon.icns` into `out_dir`, all made from
+/// `source`, so the formats can't drift apart.
+///
+/// # Arguments
+///
+/// * `source` - A square PNG. The bigger the better: it is scaled up to 1024x1024 if needed.
+/// * `out_dir` - Where to write the icons. It is created if it doesn't exist.
+///
+/// # Errors
+///
+/// Returns an error if the source can't be read or isn't square, or the icons can't be written.
+pub fn generate(source: &Path, out_dir: &Path) -> anyhow::Result<GeneratedIcons> {
+    let image = image::open(source)
+        .with_context(|| format!("Failed to read icon at {}", source.display()))?
+        .to_rgba8();
+    anyhow::ensure!(
+        image.width() == image.height(),
+        "{} must be square, not {}x{}",
+        source.display(),
+        image.width(),
+        image.height()
+    );
+
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let icons = GeneratedIcons {
+        png: out_dir.join("app-icon.png"),
+        ico: out_dir.join("app-icon.ico"),
+        icns: out_dir.join("app-icon.icns"),
+    };
+    for (path, contents) in [
+        (&icons.png, encode_png(&image)?),
+        (&icons.ico, encode_ico(&image)?),
+        (&icons.icns, encode_icns(&image)?),
+    ] {
+        fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(icons)
+}
+
+/// Scales a square image to `size`x`size`. Scaling up keeps hard pixel edges, since the source
+/// is small pixel art; scaling down smooths.
+pub fn resize(image: &RgbaImage, size: u32) -> RgbaImage {
+    let filter = if size > image.width() {
+        FilterType::Nearest
+    } else {
+        FilterType::Lanczos3
+    };
+    image::imageops::resize(image, size, size, filter)
+}
+
+/// Encodes an image as PNG.
+///
+/// # Errors
+///
+/// Returns an error if encoding fails.
+pub fn encode_png(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .context("Failed to encode PNG")?;
+    Ok(png.into_inner())
+}
+
+/// Encodes an `.ico` with every size in [`ICO_SIZES`].
+///
+/// # Errors
+///
+/// Returns an error if encoding fails.
+pub fn encode_ico(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut icon_dir = ico::IconDir::new(ico::ResourceType::Icon);
+    for size in ICO_SIZES {
+        let resized = resize(image, size);
+        let icon_image = ico::IconImage::from_rgba_data(size, size, resized.into_raw());
+        icon_dir.add_entry(
+            ico::IconDirEntry::encode(&icon_image)
+                .with_context(|| format!("Failed to encode {size}x{size} icon"))?,
+        );
+    }
+    let mut ico = Vec::new();
+    icon_dir.write(&mut ico).context("Failed to write .ico")?;
+    Ok(ico)
+}
+
+/// Encodes an `.icns` with every entry in [`ICNS_ENTRIES`].
+///
+/// The format is a big-endian header (`icns` and the file length) followed by entries, each a
+/// type, its length including the 8-byte entry header, and the data.
+///
+/// # Errors
+///
+/// Returns an error if encoding fails.
+pub fn encode_icns(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut entries = Vec::new();
+    for (kind, size) in ICNS_ENTRIES {
+        let png = encode_png(&resize(image, size))?;
+        entries.extend_from_slice(&kind);
+        entries.extend_from_slice(&icns_length(png.len() + 8)?.to_be_bytes());
+        entries.extend_from_slice(&png);
+    }
+    let mut icns = Vec::with_capacity(entries.len() + 8);
+    icns.extend_from_slice(b"icns");
+    icns.extend_from_slice(&icns_length(entries.len() + 8)?.to_be_bytes());
+    icns.extend_from_slice(&entries);
+    Ok(icns)
+}
+
+fn icns_length(length: usize) -> anyhow::Result<u32> {
+    u32::try_from(length).context("Icon is too big for an .icns file")
+}
+
+/// Splits an `.icns` file into its entries' types and data.
+///
+/// # Errors
+///
+/// Returns an error if the file is malformed.
+pub fn read_icns_entries(icns: &[u8]) -> anyhow::Result<Vec<([u8; 4], &[u8])>> {
+    let header = |at: usize| -> anyhow::Result<([u8; 4], usize)> {
+        let bytes = icns.get(at..at + 8).context("Truncated .icns header")?;
+        let kind = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        let length = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+        anyhow::ensure!(length >= 8, "Invalid .icns length {length}");
+        Ok((kind, length))
+    };
+
+    let (magic, total) = header(0)?;
+    anyhow::ensure!(&magic == b"icns", "Not an .icns file");
+    anyhow::ensure!(total == icns.len(), ".icns length doesn't match the file");
+
+    let mut entries = Vec::new();
+    let mut at = 8;
+    while at < total {
+        let (kind, length) = header(at)?;
+        let data = icns
+            .get(at + 8..at + length)
+            .context("Truncated .icns entry")?;
+        entries.push((kind, data));
+        at += length;
+    }
+    Ok(entries)
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+/// Gets how long it has been since the user last used the keyboard or mouse, if the platform
+/// can tell.
+pub fn idle_time() -> Option<Duration> {
+    platform_idle_time()
+}
+
+#[cfg(windows)]
+fn platform_idle_time() -> Option<Duration> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: u32::try_from(std::mem::size_of::<LASTINPUTINFO>()).ok()?,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return None;
+    }
+    // both are milliseconds since boot and wrap around after ~49 days
+    let now = unsafe { GetTickCount() };
+    Some(Duration::from_millis(u64::from(
+        now.wrapping_sub(info.dwTime),
+    )))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // looks like `    | |   "HIDIdleTime" = 1234567890`, in nanoseconds
+    let nanos = stdout
+        .lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))?
+        .rsplit('=')
+        .next()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_nanos(nanos))
+}
+
+/// Only X11 sessions are supported, through `xprintidle`.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("xprintidle").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let millis = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_millis(millis))
+}
@@ -0,0 +1,171 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::{
+    bundle::BundleConfig,
+    config::Config,
+    resources::{Resource, APP_ICON_PNG},
+};
+
+/// Name of the default config written into the install location.
+pub const DEFAULT_CONFIG_FILE_NAME: &str = "config.default.toml";
+
+/// The resources of this build: the PNG icon and the `.ico` and `.icns` generated from it by
+/// `build.rs`. They are compiled in, so installing with `--from-binary` doesn't need the build
+/// directory.
+pub const RESOURCES: [Resource<'static>; 3] = [
+    Resource {
+        file_name: "app-icon.png",
+        contents: APP_ICON_PNG,
+    },
+    Resource {
+        file_name: "app-icon.ico",
+        contents: include_bytes!(concat!(env!("SPOTIKILL_RESOURCE_DIR"), "/app-icon.ico")),
+    },
+    Resource {
+        file_name: "app-icon.icns",
+        contents: include_bytes!(concat!(env!("SPOTIKILL_RESOURCE_DIR"), "/app-icon.icns")),
+    },
+];
+
+/// The `Cargo.toml` this was built from, for its bundle settings.
+const CARGO_MANIFEST: &str = include_str!("../Cargo.toml");
+
+/// What installing a file did to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Unchanged,
+    Removed,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // pad() so callers can align the kind in columns
+        f.pad(match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Unchanged => "unchanged",
+            Self::Removed => "removed",
+        })
+    }
+}
+
+/// A file the installer created, updated, left alone or removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+}
+
+impl FileChange {
+    pub fn new(path: PathBuf, kind: ChangeKind) -> Self {
+        Self { path, kind }
+    }
+}
+
+/// Writes a file unless it already has exactly these contents, so re-installing the same build
+/// touches nothing.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read or written.
+pub fn write_if_changed(path: &Path, contents: &[u8]) -> anyhow::Result<FileChange> {
+    let kind = match fs::read(path) {
+        Ok(existing) if existing == contents => {
+            return Ok(FileChange::new(path.to_path_buf(), ChangeKind::Unchanged))
+        }
+        Ok(_) => ChangeKind::Updated,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => ChangeKind::Created,
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(FileChange::new(path.to_path_buf(), kind))
+}
+
+/// Copies `src` to `dest` unless `dest` is already the same, see [`write_if_changed`].
+///
+/// # Errors
+///
+/// Returns an error if either file can't be read or `dest` can't be written.
+pub fn copy_if_changed(src: &Path, dest: &Path) -> anyhow::Result<FileChange> {
+    let contents = fs::read(src).with_context(|| format!("Failed to read {}", src.display()))?;
+    write_if_changed(dest, &contents)
+}
+
+/// Reads the app bundle settings from the `Cargo.toml` this was built from.
+///
+/// # Errors
+///
+/// Returns an error if the bundle settings are invalid.
+pub fn bundle_config() -> anyhow::Result<BundleConfig> {
+    BundleConfig::from_manifest(CARGO_MANIFEST)
+        .context("Invalid [package.metadata.bundle] in Cargo.toml")
+}
+
+/// Copies every resource into `dest`, creating it if needed.
+///
+/// # Arguments
+///
+/// * `dest` - The directory to copy the resources into.
+///
+/// # Returns
+///
+/// What happened to each resource. Ones that are already up to date aren't copied again.
+///
+/// # Errors
+///
+/// Returns an error if the resources can't be written.
+pub fn copy_resources(dest: &Path) -> anyhow::Result<Vec<FileChange>> {
+    fs::create_dir_all(dest).with_context(|| format!("Failed to create {}", dest.display()))?;
+
+    RESOURCES
+        .iter()
+        .map(|resource| write_if_changed(&dest.join(resource.file_name), resource.contents))
+        .collect()
+}
+
+/// Writes the default config into `dir` as [`DEFAULT_CONFIG_FILE_NAME`], so users have a
+/// reference of every key and its default.
+///
+/// # Errors
+///
+/// Returns an error if the config can't be serialized or written.
+pub fn write_default_config(dir: &Path) -> anyhow::Result<FileChange> {
+    let contents =
+        toml::to_string_pretty(&Config::default()).context("Failed to serialize the config")?;
+    write_if_changed(&dir.join(DEFAULT_CONFIG_FILE_NAME), contents.as_bytes())
+}
+
+/// Copies the default config to the user's config path if they don't have a config yet. An
+/// existing config is never touched.
+///
+/// # Arguments
+///
+/// * `default_config` - The default config written by [`write_default_config`].
+///
+/// # Returns
+///
+/// The path of the new config, or `None` if one already existed.
+///
+/// # Errors
+///
+/// Returns an error if the config path can't be determined or the config can't be copied.
+pub fn seed_user_config(default_config: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let path = Config::path()?;
+    if path.exists() {
+        return Ok(None);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::copy(default_config, &path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Some(path))
+}
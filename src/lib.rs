@@ -1,3 +1,31 @@
 #[cfg(windows)]
 pub mod aumid;
+pub mod autostart;
+pub mod bundle;
+pub mod cache;
+pub mod config;
+pub mod confirm;
 pub mod constants;
+pub mod flavour;
+pub mod icon;
+pub mod iconset;
+pub mod idle;
+#[cfg(feature = "installer")]
+pub mod install;
+pub mod manifest;
+pub mod matcher;
+pub mod playback;
+pub mod process;
+pub mod resources;
+pub mod restore;
+pub mod role;
+pub mod safety;
+pub mod schedule;
+#[cfg(windows)]
+pub mod shortcut;
+pub mod snooze;
+#[cfg(any(windows, target_os = "macos"))]
+pub mod uninstall;
+pub mod update;
+#[cfg(windows)]
+pub mod window;
@@ -1,24 +1,66 @@
 #![warn(clippy::all, clippy::pedantic)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-use std::{path::Path, str::FromStr};
+use std::{
+    io::{self, IsTerminal, Write},
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context;
+use chrono::Local;
 use const_format::formatcp;
+use image::{ImageFormat, RgbaImage};
 use notify_rust::Notification;
-use once_cell::sync::Lazy;
-use regex::Regex;
-use spotikill::constants::{CARGO_PKG_NAME, CARGO_PKG_VERSION, ICON_PATH};
-use sysinfo::{ProcessRefreshKind, RefreshKind, System};
-use tao::event_loop::EventLoopBuilder;
+use spotikill::{
+    autostart,
+    cache::{clear_spotify_cache, CacheReport},
+    config::{project_dirs, Config},
+    confirm::{ask_with_dialog, confirm_kill},
+    constants::{CARGO_PKG_NAME, CARGO_PKG_VERSION},
+    icon::IconSet,
+    idle::idle_time,
+    manifest::InstallManifest,
+    matcher::ProcessMatcher,
+    playback::SystemPlayback,
+    process::{
+        find_spotify_pids, kill_spotify_processes, preview_kill, KillReport, SpotifyHealth,
+        SpotifyMonitor, SpotifyStatus,
+    },
+    resources::{icon_path, APP_ICON_PNG},
+    restore::{restore_after_restart, AfterRestart, SpotifySnapshot},
+    safety::KillPolicy,
+    schedule::{ScheduledAction, Scheduler},
+    snooze::{Snooze, SnoozeLength},
+    update::{check_for_update, current_version, open_in_browser, ReleaseManifest},
+};
+use tao::{
+    event::{Event, StartCause},
+    event_loop::{ControlFlow, EventLoopBuilder, EventLoopProxy},
+};
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuId, MenuItemBuilder},
-    TrayIcon, TrayIconBuilder,
+    menu::{
+        CheckMenuItem, CheckMenuItemBuilder, Menu, MenuEvent, MenuId, MenuItem, MenuItemBuilder,
+        PredefinedMenuItem, Submenu, SubmenuBuilder,
+    },
+    TrayIcon, TrayIconBuilder, TrayIconEvent,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Message {
     KillSpotify,
+    KillAndClearCache,
+    RestartSpotify,
+    /// Toggles whether the next run of the scheduled job at this index is skipped
+    ToggleScheduledRun(usize),
+    ToggleStartAtLogin,
+    /// Suspends scheduled runs for a while
+    Snooze(SnoozeLength),
+    /// Ends the snooze
+    Resume,
+    /// Opens the page of the available update
+    OpenUpdate,
     /// No-op
     Noop,
     Quit,
@@ -30,8 +72,22 @@ impl FromStr for Message {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "KillSpotify" => Ok(Self::KillSpotify),
+            "KillAndClearCache" => Ok(Self::KillAndClearCache),
+            "RestartSpotify" => Ok(Self::RestartSpotify),
+            "ToggleStartAtLogin" => Ok(Self::ToggleStartAtLogin),
+            "Snooze:hour" => Ok(Self::Snooze(SnoozeLength::Hour)),
+            "Snooze:tomorrow" => Ok(Self::Snooze(SnoozeLength::UntilTomorrow)),
+            "Snooze:indefinitely" => Ok(Self::Snooze(SnoozeLength::Indefinitely)),
+            "Resume" => Ok(Self::Resume),
+            "OpenUpdate" => Ok(Self::OpenUpdate),
             "Quit" => Ok(Self::Quit),
-            _ => Err(anyhow::anyhow!("Invalid message: {s}")),
+            _ => {
+                let index = s
+                    .strip_prefix("ToggleScheduledRun:")
+                    .and_then(|index| index.parse().ok())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid message: {s}"))?;
+                Ok(Self::ToggleScheduledRun(index))
+            }
         }
     }
 }
@@ -48,6 +104,15 @@ impl std::fmt::Display for Message {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
             Self::KillSpotify => "KillSpotify",
+            Self::KillAndClearCache => "KillAndClearCache",
+            Self::RestartSpotify => "RestartSpotify",
+            Self::ToggleScheduledRun(index) => return write!(f, "ToggleScheduledRun:{index}"),
+            Self::ToggleStartAtLogin => "ToggleStartAtLogin",
+            Self::Snooze(SnoozeLength::Hour) => "Snooze:hour",
+            Self::Snooze(SnoozeLength::UntilTomorrow) => "Snooze:tomorrow",
+            Self::Snooze(SnoozeLength::Indefinitely) => "Snooze:indefinitely",
+            Self::Resume => "Resume",
+            Self::OpenUpdate => "OpenUpdate",
             Self::Quit => "Quit",
             Self::Noop => "No-op",
         };
@@ -55,23 +120,44 @@ impl std::fmt::Display for Message {
     }
 }
 
+impl Message {
+    /// Whether this message kills Spotify, and therefore may need confirming first.
+    fn kills_spotify(self) -> bool {
+        matches!(
+            self,
+            Self::KillSpotify | Self::KillAndClearCache | Self::RestartSpotify
+        )
+    }
+}
+
+impl From<ScheduledAction> for Message {
+    fn from(action: ScheduledAction) -> Self {
+        match action {
+            ScheduledAction::Kill => Self::KillSpotify,
+            ScheduledAction::Restart => Self::RestartSpotify,
+        }
+    }
+}
+
 /// Gets a base notification with the app name and icon set.
 #[cfg(windows)]
 fn get_base_notification() -> Notification {
     const AUMID: &str = spotikill::aumid::get_aumid();
 
+    let mut notification = Notification::new();
+    notification.app_id(AUMID).appname(CARGO_PKG_NAME);
+    // a notification without an icon beats no notification at all
+    if let Ok(icon_path) = icon_path() {
+        notification.icon(&icon_path.to_string_lossy());
+    }
     // both finalize() and to_owned() just call clone() on the
     // builder, so it doesn't matter which one we use.
     // I just chose finalize() because it's a cool name.
-    Notification::new()
-        .app_id(AUMID)
-        .appname(CARGO_PKG_NAME)
-        .icon(ICON_PATH)
-        .finalize()
+    notification.finalize()
 }
 
-/// Gets a base notification. On macOS, this just returns [`Notification::new()`].
-#[cfg(target_os = "macos")]
+/// Gets a base notification. On macOS and Linux, this just returns [`Notification::new()`].
+#[cfg(not(windows))]
 #[allow(clippy::inline_always)]
 #[inline(always)]
 fn get_base_notification() -> Notification {
@@ -100,37 +186,103 @@ fn show_simple_notification<S: AsRef<str>>(title: S, body: S) {
         .unwrap_or_else(|e| unreachable!("Failed to show notification: {e:#?}"));
 }
 
-fn kill_spotify_processes() -> anyhow::Result<()> {
-    static SPOTIFY_REGEX: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"([sS]potify)([ \w()]*)(\.exe)?").unwrap());
+/// What killing Spotify from the tray did. Killing runs on a worker thread, which sends this back
+/// to the event loop to tell the user.
+#[derive(Debug)]
+enum KillOutcome {
+    Killed(KillReport),
+    /// The kill report is missing if Spotify wasn't running.
+    ClearedCache(Option<KillReport>, CacheReport),
+    Restarted(KillReport),
+}
 
-    let s =
-        System::new_with_specifics(RefreshKind::new().with_processes(ProcessRefreshKind::new()));
+impl KillOutcome {
+    /// Shows a notification about what was done.
+    fn notify(&self) {
+        match self {
+            Self::Killed(report) => {
+                let title = if report.respawned.is_empty() {
+                    "Spotify Killed"
+                } else {
+                    "Spotify Came Back"
+                };
+                show_simple_notification(title, &report.summary());
+            }
+            Self::ClearedCache(kill_report, cache_report) => {
+                let mut body = kill_report
+                    .as_ref()
+                    .map(|report| format!("{}\n", report.summary()))
+                    .unwrap_or_default();
+                body.push_str(&cache_report.summary());
+                show_simple_notification("Spotify Cache Cleared", body.as_str());
+            }
+            Self::Restarted(report) => {
+                show_simple_notification("Spotify Restarted", &report.summary());
+            }
+        }
+    }
+}
 
-    let spotify_procs: Vec<_> = s
-        .processes()
-        .values()
-        .filter(|proc| {
-            let name = proc.name();
-            SPOTIFY_REGEX.is_match(name)
-        })
-        .collect();
+fn kill_spotify(matcher: &ProcessMatcher, policy: KillPolicy) -> anyhow::Result<KillOutcome> {
+    let report = kill_spotify_processes(matcher, policy)?;
+
+    #[cfg(debug_assertions)]
+    print!("{report}");
 
-    anyhow::ensure!(!spotify_procs.is_empty(), "No Spotify processes found");
+    Ok(KillOutcome::Killed(report))
+}
 
-    let proc_count = spotify_procs.len();
+/// Kills Spotify if it is running, then clears its cache.
+fn kill_and_clear_cache(
+    matcher: &ProcessMatcher,
+    policy: KillPolicy,
+    include_offline: bool,
+) -> anyhow::Result<KillOutcome> {
+    let kill_report = if find_spotify_pids(matcher).is_empty() {
+        None
+    } else {
+        let report = kill_spotify_processes(matcher, policy)?;
 
-    for proc in &spotify_procs {
         #[cfg(debug_assertions)]
-        println!("Killing process: {} ({})", proc.name(), proc.pid());
+        print!("{report}");
 
-        proc.kill();
-        proc.wait();
-    }
+        Some(report)
+    };
+
+    let cache_report = clear_spotify_cache(include_offline)?;
 
-    show_simple_notification("Spotify Killed", &format!("{proc_count} total processes"));
+    #[cfg(debug_assertions)]
+    println!("{cache_report:#?}");
 
-    Ok(())
+    Ok(KillOutcome::ClearedCache(kill_report, cache_report))
+}
+
+/// Restarts Spotify, then restores its window and playback in the background if the config says
+/// to.
+fn restart_spotify(
+    matcher: &ProcessMatcher,
+    policy: KillPolicy,
+    after_restart: &AfterRestart,
+) -> anyhow::Result<KillOutcome> {
+    let snapshot = after_restart
+        .is_enabled()
+        .then(|| SpotifySnapshot::take(matcher, after_restart));
+    let report = spotikill::process::restart_spotify(matcher, policy)?;
+
+    #[cfg(debug_assertions)]
+    print!("{report}");
+
+    if let Some(snapshot) = snapshot {
+        let matcher = matcher.clone();
+        let after_restart = after_restart.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = restore_after_restart(&matcher, snapshot, &after_restart) {
+                show_error_notification(&err);
+            }
+        });
+    }
+
+    Ok(KillOutcome::Restarted(report))
 }
 
 fn show_error_notification<E>(err: &E)
@@ -144,18 +296,166 @@ where
         .unwrap();
 }
 
-fn load_tray_icon<P: AsRef<Path>>(src: P) -> anyhow::Result<tray_icon::Icon> {
+fn load_icon_image<P: AsRef<Path>>(src: P) -> anyhow::Result<RgbaImage> {
     let src = src.as_ref();
     let icon_data = image::open(src).with_context(|| format!("Failed to read icon at {src:?}"))?;
-    let rgba8_data = icon_data.to_rgba8();
-    let (width, height) = rgba8_data.dimensions();
-    let rgba8_data = rgba8_data.into_raw();
+    Ok(icon_data.to_rgba8())
+}
+
+/// Loads the app icon for the tray from the resource directory, or uses the copy compiled into
+/// the executable if that can't be read.
+fn load_tray_image() -> anyhow::Result<RgbaImage> {
+    if let Ok(image) = icon_path().and_then(load_icon_image) {
+        return Ok(image);
+    }
+    let icon_data = image::load_from_memory_with_format(APP_ICON_PNG, ImageFormat::Png)
+        .context("Failed to read the embedded icon")?;
+    Ok(icon_data.to_rgba8())
+}
+
+fn to_tray_icon(image: &RgbaImage) -> anyhow::Result<tray_icon::Icon> {
+    let (width, height) = image.dimensions();
+    let rgba8_data = image.as_raw().clone();
 
     tray_icon::Icon::from_rgba(rgba8_data, width, height)
         .context("Failed to create tray icon from RGBA8 data.")
 }
 
-fn build_tray_menu() -> anyhow::Result<Menu> {
+/// The tray icon along with the menu items that change at runtime.
+struct Tray {
+    icon: TrayIcon,
+    menu: Menu,
+    icon_set: IconSet,
+    /// The health the current icon represents.
+    health: SpotifyHealth,
+    /// Whether the current icon shows the pause badge.
+    snoozed: bool,
+    status_item: MenuItem,
+    kill_spotify_item: MenuItem,
+    restart_spotify_item: MenuItem,
+    /// Lists upcoming scheduled runs. Only added to the menu if there are any schedules.
+    schedule_submenu: Submenu,
+    /// Only enabled while snoozed.
+    resume_item: MenuItem,
+    start_at_login_item: CheckMenuItem,
+    /// Only added to the menu once an update is found.
+    update_item: MenuItem,
+    update_shown: bool,
+}
+
+impl Tray {
+    /// Reflects the given Spotify status and snooze in the icon, tooltip and menu.
+    fn update_status(
+        &mut self,
+        status: &SpotifyStatus,
+        config: &Config,
+        snooze: Snooze,
+    ) -> anyhow::Result<()> {
+        let status_text = status.to_string();
+        self.status_item.set_text(&status_text);
+        self.kill_spotify_item.set_enabled(status.is_running());
+        self.restart_spotify_item.set_enabled(status.is_running());
+        let now = Local::now();
+        let mut tooltip = format!("{CARGO_PKG_NAME}\n{status_text}");
+        if let Some(snooze_text) = snooze.describe(now) {
+            tooltip = format!("{tooltip}\n{snooze_text}");
+        }
+        self.icon
+            .set_tooltip(Some(tooltip))
+            .context("Failed to set tray tooltip.")?;
+
+        let health = status.health(config);
+        let snoozed = snooze.is_active(now);
+        self.resume_item.set_enabled(snoozed);
+        if health != self.health || snoozed != self.snoozed {
+            let icon = to_tray_icon(self.icon_set.for_state(health, snoozed))?;
+            self.icon
+                .set_icon(Some(icon))
+                .context("Failed to set tray icon.")?;
+            self.health = health;
+            self.snoozed = snoozed;
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the scheduled runs submenu. Unchecking a run skips it.
+    fn update_schedule(&self, scheduler: &Scheduler) -> anyhow::Result<()> {
+        while self.schedule_submenu.remove_at(0).is_some() {}
+        for (index, job) in scheduler.jobs().iter().enumerate() {
+            let item = CheckMenuItemBuilder::new()
+                .text(job.describe())
+                .id(Message::ToggleScheduledRun(index).into())
+                .enabled(job.next_run.is_some())
+                .checked(!job.skip_next)
+                .build();
+            self.schedule_submenu.append(&item)?;
+        }
+        Ok(())
+    }
+
+    /// Shows the available update at the top of the menu.
+    fn show_update(&mut self, manifest: &ReleaseManifest) -> anyhow::Result<()> {
+        self.update_item
+            .set_text(format!("Update available: v{}", manifest.version));
+        self.update_item.set_enabled(manifest.url.is_some());
+        if !self.update_shown {
+            self.menu.insert(&self.update_item, 0)?;
+            self.update_shown = true;
+        }
+        Ok(())
+    }
+
+    /// Checks "Start at login" if spotikill currently starts at login.
+    fn update_start_at_login(&self) -> anyhow::Result<()> {
+        self.start_at_login_item
+            .set_checked(autostart::is_enabled()?);
+        Ok(())
+    }
+}
+
+/// Builds the "Snooze scheduled runs" submenu, returning it and its "Resume" item.
+fn build_snooze_submenu() -> anyhow::Result<(Submenu, MenuItem)> {
+    let snooze_items = [
+        ("For 1 hour", SnoozeLength::Hour),
+        ("Until tomorrow", SnoozeLength::UntilTomorrow),
+        ("Indefinitely", SnoozeLength::Indefinitely),
+    ]
+    .map(|(text, length)| {
+        MenuItemBuilder::new()
+            .text(text)
+            .id(Message::Snooze(length).into())
+            .enabled(true)
+            .build()
+    });
+    let resume_item = MenuItemBuilder::new()
+        .text("Resume")
+        .id(Message::Resume.into())
+        .enabled(false)
+        .build();
+    let snooze_submenu = SubmenuBuilder::new()
+        .text("Snooze scheduled runs")
+        .enabled(true)
+        .items(&[
+            &snooze_items[0],
+            &snooze_items[1],
+            &snooze_items[2],
+            &PredefinedMenuItem::separator(),
+            &resume_item,
+        ])
+        .build()?;
+    Ok((snooze_submenu, resume_item))
+}
+
+fn build_tray(scheduler: &Scheduler) -> anyhow::Result<Tray> {
+    let icon_set = IconSet::new(load_tray_image()?);
+    // until the first status refresh, assume the best
+    let health = SpotifyHealth::Healthy;
+    let icon = to_tray_icon(icon_set.for_health(health))?;
+
+    let status_item = MenuItemBuilder::new()
+        .text("Checking Spotify status...")
+        .enabled(false)
+        .build();
     let quit_item = MenuItemBuilder::new()
         .text("Quit")
         .id(Message::Quit.into())
@@ -166,24 +466,362 @@ fn build_tray_menu() -> anyhow::Result<Menu> {
         .id(Message::KillSpotify.into())
         .enabled(true)
         .build();
+    let kill_and_clear_cache_item = MenuItemBuilder::new()
+        .text("Kill and clear cache")
+        .id(Message::KillAndClearCache.into())
+        .enabled(true)
+        .build();
+    let restart_spotify_item = MenuItemBuilder::new()
+        .text("Restart Spotify")
+        .id(Message::RestartSpotify.into())
+        .enabled(true)
+        .build();
+    let schedule_submenu = SubmenuBuilder::new()
+        .text("Scheduled runs")
+        .enabled(true)
+        .build()?;
+    let (snooze_submenu, resume_item) = build_snooze_submenu()?;
+    let start_at_login_item = CheckMenuItemBuilder::new()
+        .text("Start at login")
+        .id(Message::ToggleStartAtLogin.into())
+        .enabled(true)
+        .checked(autostart::is_enabled()?)
+        .build();
+    let update_item = MenuItemBuilder::new()
+        .text("Update available")
+        .id(Message::OpenUpdate.into())
+        .enabled(true)
+        .build();
     let menu = Menu::new();
-    menu.append_items(&[&kill_spotify_item, &quit_item])?;
-    Ok(menu)
-}
+    menu.append_items(&[
+        &status_item,
+        &PredefinedMenuItem::separator(),
+        &kill_spotify_item,
+        &kill_and_clear_cache_item,
+        &restart_spotify_item,
+    ])?;
+    if !scheduler.is_empty() {
+        menu.append(&schedule_submenu)?;
+    }
+    menu.append(&snooze_submenu)?;
+    menu.append_items(&[
+        &PredefinedMenuItem::separator(),
+        &start_at_login_item,
+        &quit_item,
+    ])?;
 
-fn build_tray() -> anyhow::Result<TrayIcon> {
-    // TODO: bundle icon png with installer
-    let icon = load_tray_icon(ICON_PATH)?;
-    let menu = build_tray_menu()?;
-    TrayIconBuilder::new()
-        .with_menu(Box::new(menu))
+    let icon = TrayIconBuilder::new()
+        .with_menu(Box::new(menu.clone()))
         .with_tooltip(CARGO_PKG_NAME)
         .with_icon(icon)
         .build()
-        .context("Failed to build tray icon.")
+        .context("Failed to build tray icon.")?;
+
+    let tray = Tray {
+        icon,
+        menu,
+        icon_set,
+        health,
+        snoozed: false,
+        status_item,
+        kill_spotify_item,
+        restart_spotify_item,
+        schedule_submenu,
+        resume_item,
+        start_at_login_item,
+        update_item,
+        update_shown: false,
+    };
+    tray.update_schedule(scheduler)?;
+    Ok(tray)
+}
+
+/// Events sent to the event loop from other threads.
+#[derive(Debug)]
+enum UserEvent {
+    Menu(MenuEvent),
+    /// The tray icon was clicked, which usually opens the menu.
+    TrayIcon,
+    /// The update checker found a newer release.
+    UpdateAvailable(ReleaseManifest),
+    /// The worker thread started by [`App::spawn_kill`] is done.
+    KillFinished(anyhow::Result<KillOutcome>),
+}
+
+/// Checks the release feed in the background, now and then every `interval`. Only newer releases
+/// are sent to the event loop, failed checks are ignored until the next one.
+fn spawn_update_checker(feed: String, interval: Duration, proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || {
+        let current = current_version();
+        loop {
+            match check_for_update(&feed, &current) {
+                Ok(Some(manifest)) => {
+                    if proxy
+                        .send_event(UserEvent::UpdateAvailable(manifest))
+                        .is_err()
+                    {
+                        // the event loop is gone
+                        return;
+                    }
+                }
+                Ok(None) => {}
+                #[allow(unused_variables)]
+                Err(err) => {
+                    #[cfg(debug_assertions)]
+                    println!("Update check failed: {err:#}");
+                }
+            }
+            std::thread::sleep(interval);
+        }
+    });
+}
+
+/// Everything the event loop needs to keep between iterations.
+struct App {
+    config: Config,
+    matcher: ProcessMatcher,
+    policy: KillPolicy,
+    // using an Option to allow the tray to be dropped when the event loop exits
+    tray: Option<Tray>,
+    monitor: SpotifyMonitor,
+    status: SpotifyStatus,
+    scheduler: Scheduler,
+    /// Whether scheduled runs are suspended.
+    snooze: Snooze,
+    next_refresh: Instant,
+    /// The newest release found by the update checker, if it's newer than this build.
+    update: Option<ReleaseManifest>,
+    proxy: EventLoopProxy<UserEvent>,
+    /// Whether a worker thread is killing Spotify right now.
+    killing: bool,
+}
+
+impl App {
+    /// Refreshes Spotify's status and shows it in the tray.
+    fn refresh(&mut self) {
+        self.status = self.monitor.refresh();
+        let snooze = self.snooze.at(Local::now());
+        if snooze != self.snooze {
+            // ran out, so it doesn't come back after a restart either
+            self.set_snooze(snooze);
+        }
+        self.update_status();
+        // the autostart entry may have been changed behind our back, e.g. by the installer
+        self.update_start_at_login();
+        self.next_refresh = Instant::now() + self.config.status_refresh_interval();
+    }
+
+    fn update_status(&mut self) {
+        if let Some(tray) = &mut self.tray {
+            if let Err(err) = tray.update_status(&self.status, &self.config, self.snooze) {
+                show_error_notification(&err);
+            }
+        }
+    }
+
+    /// Starts or ends a snooze, saving it so it survives restarts.
+    fn set_snooze(&mut self, snooze: Snooze) {
+        self.snooze = snooze;
+        if let Err(err) = snooze.save() {
+            show_error_notification(&err);
+        }
+        self.update_status();
+    }
+
+    /// Performs every scheduled run that is due, if its conditions allow it. While snoozed, due
+    /// runs are skipped rather than saved up for when the snooze ends.
+    fn run_due_schedules(&mut self) {
+        let now = Local::now();
+        // the menu only changes when a job runs, is skipped or is postponed
+        if !self.scheduler.has_due_jobs(now) {
+            return;
+        }
+        let due = self.scheduler.due_jobs(now);
+        let snoozed = self.snooze.is_active(now);
+        for &index in &due {
+            if snoozed {
+                self.scheduler.complete(index, now);
+                continue;
+            }
+
+            let job_config = &self.scheduler.jobs()[index].config;
+            let playback = SystemPlayback::new(&self.matcher);
+            let idle_threshold = self.config.idle_threshold();
+            if !job_config.conditions_met(idle_time(), idle_threshold, &playback) {
+                self.scheduler.postpone(index, now);
+                continue;
+            }
+
+            let action = job_config.action;
+            self.scheduler.complete(index, now);
+            // there is nothing to kill or restart, wait for the next run
+            if self.status.is_running() {
+                // scheduled runs are confirmed by their conditions, not by the user
+                self.perform(action.into());
+            }
+        }
+        self.update_schedule();
+    }
+
+    /// Tells the user about a new release, once per version.
+    fn update_available(&mut self, manifest: ReleaseManifest) {
+        if self.update.as_ref() == Some(&manifest) {
+            return;
+        }
+        let body = manifest.notes.clone().unwrap_or_else(|| {
+            format!(
+                "{CARGO_PKG_NAME} v{} is available, you have v{}.",
+                manifest.version,
+                current_version()
+            )
+        });
+        show_simple_notification(
+            format!("{CARGO_PKG_NAME} v{} is available", manifest.version),
+            body,
+        );
+        if let Some(tray) = &mut self.tray {
+            if let Err(err) = tray.show_update(&manifest) {
+                show_error_notification(&err);
+            }
+        }
+        self.update = Some(manifest);
+    }
+
+    fn update_start_at_login(&self) {
+        if let Some(tray) = &self.tray {
+            if let Err(err) = tray.update_start_at_login() {
+                show_error_notification(&err);
+            }
+        }
+    }
+
+    fn update_schedule(&self) {
+        if let Some(tray) = &self.tray {
+            if let Err(err) = tray.update_schedule(&self.scheduler) {
+                show_error_notification(&err);
+            }
+        }
+    }
+
+    /// Asks the user to confirm a kill if the config says to. Returns `true` if it should go ahead.
+    fn confirm_kill(&self) -> bool {
+        confirm_kill(
+            &self.matcher,
+            self.policy,
+            self.config.confirm_kill,
+            &SystemPlayback::new(&self.matcher),
+            ask_with_dialog,
+        )
+        .unwrap_or_else(|err| {
+            show_error_notification(&err);
+            false
+        })
+    }
+
+    /// Handles a message from the menu, asking for confirmation first if needed. Returns `true`
+    /// if the app should exit.
+    fn handle_message(&mut self, msg: Message) -> bool {
+        if msg.kills_spotify() && !self.confirm_kill() {
+            return false;
+        }
+        self.perform(msg)
+    }
+
+    /// Kills, clears the cache or restarts Spotify on a worker thread, so waiting for Spotify to
+    /// exit doesn't freeze the tray. The outcome comes back as [`UserEvent::KillFinished`]. Does
+    /// nothing while an earlier kill is still running.
+    fn spawn_kill(&mut self, msg: Message) {
+        if self.killing {
+            return;
+        }
+        self.killing = true;
+
+        let matcher = self.matcher.clone();
+        let policy = self.policy;
+        let include_offline = self.config.clear_offline_downloads;
+        let after_restart = self.config.after_restart.clone();
+        let proxy = self.proxy.clone();
+        std::thread::spawn(move || {
+            let outcome = match msg {
+                Message::KillSpotify => kill_spotify(&matcher, policy),
+                Message::KillAndClearCache => {
+                    kill_and_clear_cache(&matcher, policy, include_offline)
+                }
+                Message::RestartSpotify => restart_spotify(&matcher, policy, &after_restart),
+                _ => unreachable!("{msg:?} doesn't kill Spotify"),
+            };
+            // the event loop is only gone if the app is exiting
+            let _ = proxy.send_event(UserEvent::KillFinished(outcome));
+        });
+    }
+
+    /// Tells the user what the worker thread started by [`App::spawn_kill`] did.
+    fn kill_finished(&mut self, outcome: anyhow::Result<KillOutcome>) {
+        self.killing = false;
+        match outcome {
+            Ok(outcome) => outcome.notify(),
+            Err(err) => show_error_notification(&err),
+        }
+        // refresh right away instead of showing stale status until the next tick
+        self.next_refresh = Instant::now();
+    }
+
+    /// Does whatever a message says without asking. Returns `true` if the app should exit.
+    fn perform(&mut self, msg: Message) -> bool {
+        match msg {
+            Message::KillSpotify | Message::KillAndClearCache | Message::RestartSpotify => {
+                self.spawn_kill(msg);
+            }
+            Message::ToggleScheduledRun(index) => {
+                self.scheduler.toggle_skip(index);
+                self.update_schedule();
+            }
+            Message::Snooze(length) => self.set_snooze(Snooze::start(length, Local::now())),
+            Message::Resume => self.set_snooze(Snooze::Off),
+            Message::ToggleStartAtLogin => {
+                let result =
+                    autostart::is_enabled().and_then(|enabled| autostart::set_enabled(!enabled));
+                if let Err(err) = result {
+                    show_error_notification(&err);
+                }
+                // the item checks itself when clicked, even if that failed
+                self.update_start_at_login();
+            }
+            Message::OpenUpdate => {
+                // only ever point the user at the release, installing it is up to them
+                let url = self
+                    .update
+                    .as_ref()
+                    .and_then(|update| update.url.as_deref());
+                if let Err(err) = url.map_or(Ok(()), open_in_browser) {
+                    show_error_notification(&err);
+                }
+            }
+            Message::Quit => {
+                let _ = self.tray.take();
+                return true;
+            }
+            Message::Noop => {}
+        }
+        false
+    }
+
+    /// When the event loop should wake up next, either to refresh or to run a schedule.
+    fn next_wakeup(&self) -> Instant {
+        let next_schedule = self.scheduler.next_wakeup().map(|next_run| {
+            let until = (next_run - Local::now()).to_std().unwrap_or_default();
+            Instant::now() + until
+        });
+        next_schedule.map_or(self.next_refresh, |next_schedule| {
+            next_schedule.min(self.next_refresh)
+        })
+    }
 }
 
 fn inner_main() -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let scheduler = Scheduler::new(&config.schedule, Local::now())?;
+
     let title = format!("{CARGO_PKG_NAME} started!");
     let body =
         format!("{CARGO_PKG_NAME} v{CARGO_PKG_VERSION} has started and is running in the tray.");
@@ -191,53 +829,310 @@ fn inner_main() -> anyhow::Result<()> {
 
     // These MUST be done in this order
     // at least on mac, the event loop builder initializes NSApp which is required
-    let event_loop = EventLoopBuilder::new().build();
-    // using an Option to allow the tray to be moved into the event loop closure
-    // and subsequently dropped when the event loop exits
-    let mut tray = Some(build_tray()?);
-
-    let menu_channel = MenuEvent::receiver();
-
-    event_loop.run(move |_event, _window, control_flow| {
-        *control_flow = tao::event_loop::ControlFlow::Poll;
-
-        if let Ok(event) = menu_channel.try_recv() {
-            #[cfg(debug_assertions)]
-            println!("Received event: {:#?}", &event);
-
-            let msg = Message::try_from(event.id).unwrap_or_else(|e| {
-                let error_msg = anyhow::anyhow!("Got bad menu event ID: {:#?}", e);
-                show_error_notification(&error_msg);
-                Message::Noop
-            });
-
-            match msg {
-                Message::KillSpotify => {
-                    if let Err(err) = kill_spotify_processes() {
-                        show_error_notification(&err);
-                    }
+    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
+    let tray = build_tray(&scheduler)?;
+
+    // forward menu events into the event loop so it can sleep between status refreshes
+    let proxy = event_loop.create_proxy();
+    MenuEvent::set_event_handler(Some(move |event| {
+        let _ = proxy.send_event(UserEvent::Menu(event));
+    }));
+    if let Some(feed) = &config.update_feed {
+        spawn_update_checker(
+            feed.clone(),
+            config.update_check_interval(),
+            event_loop.create_proxy(),
+        );
+    }
+    let proxy = event_loop.create_proxy();
+    TrayIconEvent::set_event_handler(Some(move |_| {
+        let _ = proxy.send_event(UserEvent::TrayIcon);
+    }));
+
+    // a broken snooze file shouldn't keep spotikill from starting
+    let snooze = Snooze::load().unwrap_or_else(|err| {
+        show_error_notification(&err);
+        Snooze::Off
+    });
+
+    let matcher = ProcessMatcher::from_config(&config);
+    let mut app = App {
+        monitor: SpotifyMonitor::new(matcher.clone()),
+        policy: KillPolicy::from_config(&config),
+        config,
+        matcher,
+        tray: Some(tray),
+        status: SpotifyStatus::default(),
+        scheduler,
+        snooze,
+        next_refresh: Instant::now(),
+        update: None,
+        proxy: event_loop.create_proxy(),
+        killing: false,
+    };
+
+    event_loop.run(move |event, _window, control_flow| {
+        match event {
+            Event::NewEvents(StartCause::Init | StartCause::ResumeTimeReached { .. }) => {
+                if Instant::now() >= app.next_refresh {
+                    app.refresh();
                 }
-                Message::Quit => {
-                    // explicitly dropping won't work since the closure would own the tray
-                    let _ = tray.take();
-                    *control_flow = tao::event_loop::ControlFlow::Exit;
+                app.run_due_schedules();
+            }
+            Event::UserEvent(UserEvent::Menu(event)) => {
+                #[cfg(debug_assertions)]
+                println!("Received event: {:#?}", &event);
+
+                let msg = Message::try_from(event.id).unwrap_or_else(|e| {
+                    let error_msg = anyhow::anyhow!("Got bad menu event ID: {:#?}", e);
+                    show_error_notification(&error_msg);
+                    Message::Noop
+                });
+
+                if app.handle_message(msg) {
+                    *control_flow = ControlFlow::Exit;
+                    return;
                 }
-                Message::Noop => {}
             }
+            Event::UserEvent(UserEvent::TrayIcon) => app.update_start_at_login(),
+            Event::UserEvent(UserEvent::UpdateAvailable(manifest)) => {
+                app.update_available(manifest);
+            }
+            Event::UserEvent(UserEvent::KillFinished(outcome)) => app.kill_finished(outcome),
+            _ => {}
         }
+
+        *control_flow = ControlFlow::WaitUntil(app.next_wakeup());
     });
 }
 
+#[cfg(windows)]
+const UNINSTALL_USAGE: &str =
+    "\n  uninstall  Remove spotikill, its shortcuts and its Apps & features entry";
+#[cfg(target_os = "macos")]
+const UNINSTALL_USAGE: &str = "\n  uninstall  Remove spotikill.app and its LaunchAgent";
+#[cfg(all(unix, not(target_os = "macos")))]
+const UNINSTALL_USAGE: &str = "";
+const USAGE: &str = formatcp!(
+    "Usage: {CARGO_PKG_NAME} [command [--yes]]
+
+Without a command, {CARGO_PKG_NAME} runs in the tray.
+
+Commands:
+  kill       Kill Spotify and exit
+  preview    Show which Spotify processes kill would stop and what they do, without killing
+  status     Show what is installed and where, and which files were changed{UNINSTALL_USAGE}
+
+Options:
+  -y, --yes  Don't ask for confirmation, whatever the config says
+  -h, --help Show this message"
+);
+
+/// Asks a yes/no question on the terminal, or with a dialog if there is no terminal.
+fn ask_on_terminal(question: &str) -> anyhow::Result<bool> {
+    // the shell doesn't wait for a release build on Windows, so it would read the answer itself
+    if cfg!(all(windows, not(debug_assertions))) || !io::stdin().is_terminal() {
+        return ask_with_dialog(question);
+    }
+    print!("{question} [y/N] ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Kills Spotify from the command line. Returns `false` if the user cancelled.
+fn cli_kill(yes: bool) -> anyhow::Result<bool> {
+    let config = Config::load()?;
+    let matcher = ProcessMatcher::from_config(&config);
+    let policy = KillPolicy::from_config(&config);
+    let playback = SystemPlayback::new(&matcher);
+    if !yes
+        && !confirm_kill(
+            &matcher,
+            policy,
+            config.confirm_kill,
+            &playback,
+            ask_on_terminal,
+        )?
+    {
+        return Ok(false);
+    }
+
+    let report = kill_spotify_processes(&matcher, policy)?;
+    print!("{report}");
+    Ok(true)
+}
+
+/// Shows what killing Spotify would do without killing anything.
+fn cli_preview() -> anyhow::Result<bool> {
+    let config = Config::load()?;
+    let matcher = ProcessMatcher::from_config(&config);
+    let policy = KillPolicy::from_config(&config);
+    print!("{}", preview_kill(&matcher, policy));
+    Ok(true)
+}
+
+/// Prints the install manifest and checks every installed file against it.
+fn cli_status() -> anyhow::Result<bool> {
+    let path = InstallManifest::path()?;
+    let Some(manifest) = InstallManifest::read(&path)? else {
+        println!(
+            "{CARGO_PKG_NAME} wasn't installed with the installer, there is no {}",
+            path.display()
+        );
+        return Ok(true);
+    };
+
+    println!(
+        "{CARGO_PKG_NAME} {} installed in {} on {}",
+        manifest.version,
+        manifest.install_dir.display(),
+        manifest
+            .installed_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+    );
+    println!("Manifest: {}\n", path.display());
+    for file in &manifest.files {
+        let status = file
+            .status()
+            .with_context(|| format!("Failed to read {}", file.path.display()))?;
+        println!("  {status:<8}  {}", file.path.display());
+    }
+    Ok(true)
+}
+
+/// Removes the installed spotikill after asking, unless `yes`. Returns `false` if the user said no.
+#[cfg(any(windows, target_os = "macos"))]
+fn cli_uninstall(yes: bool) -> anyhow::Result<bool> {
+    use spotikill::uninstall::Uninstalled;
+
+    let question = formatcp!("Uninstall {CARGO_PKG_NAME}? Your config is kept.");
+    if !yes && !ask_on_terminal(question)? {
+        return Ok(false);
+    }
+    match spotikill::uninstall::uninstall()? {
+        Uninstalled::Recorded {
+            install_dir,
+            report,
+        } => {
+            let install_dir_fate = if cfg!(windows) {
+                format!(
+                    "{} will be deleted if nothing else is in it",
+                    install_dir.display()
+                )
+            } else if install_dir.exists() {
+                format!("kept {}, something else is in it", install_dir.display())
+            } else {
+                format!("deleted {}", install_dir.display())
+            };
+            println!(
+                "Uninstalled, removed {} files, {install_dir_fate}",
+                report.removed.len()
+            );
+            for path in &report.kept_modified {
+                println!(
+                    "Kept {}, it was changed since it was installed",
+                    path.display()
+                );
+            }
+        }
+        Uninstalled::Unrecorded { install_dir } => {
+            let when = if cfg!(windows) { "will be" } else { "was" };
+            println!("Uninstalled, {} {when} deleted", install_dir.display());
+        }
+        Uninstalled::NotInstalled => {
+            let removed = if cfg!(windows) {
+                "the shortcuts"
+            } else {
+                "the LaunchAgent"
+            };
+            println!("Removed {removed}, {CARGO_PKG_NAME} wasn't registered as installed");
+        }
+    }
+    Ok(true)
+}
+
+/// Release builds on Windows start without a console, so everything they print is lost. Prints to
+/// the console of the shell the command was run from instead, if there is one.
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // fails when started from Explorer, which has no console to print to anyway
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+/// Runs the command given on the command line. Returns the exit code.
+fn run_cli(args: &[String]) -> i32 {
+    enum Command {
+        Kill,
+        Preview,
+        Status,
+        #[cfg(any(windows, target_os = "macos"))]
+        Uninstall,
+    }
+
+    let mut command = None;
+    let mut yes = false;
+    for arg in args {
+        match arg.as_str() {
+            "kill" => command = Some(Command::Kill),
+            "preview" => command = Some(Command::Preview),
+            "status" => command = Some(Command::Status),
+            #[cfg(any(windows, target_os = "macos"))]
+            "uninstall" => command = Some(Command::Uninstall),
+            "-y" | "--yes" => yes = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return 0;
+            }
+            _ => {
+                eprintln!("Unknown argument: {arg}\n\n{USAGE}");
+                return 2;
+            }
+        }
+    }
+
+    let result = match command {
+        Some(Command::Kill) => cli_kill(yes),
+        Some(Command::Preview) => cli_preview(),
+        Some(Command::Status) => cli_status(),
+        #[cfg(any(windows, target_os = "macos"))]
+        Some(Command::Uninstall) => cli_uninstall(yes),
+        None => {
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+    match result {
+        Ok(true) => 0,
+        Ok(false) => {
+            eprintln!("Cancelled");
+            1
+        }
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            1
+        }
+    }
+}
+
 fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        #[cfg(all(windows, not(debug_assertions)))]
+        attach_parent_console();
+        std::process::exit(run_cli(&args));
+    }
+
     // TODO: add logging
     if let Err(e) = inner_main() {
         show_error_notification(&e);
-        // save error to file
-        let error_file_path = formatcp!(
-            "{}{}error.txt",
-            env!("CARGO_MANIFEST_DIR"),
-            std::path::MAIN_SEPARATOR
-        );
+        // save error to file, next to the logs of a normal install rather than the source tree
+        let error_file_path = project_dirs().unwrap().data_local_dir().join("error.txt");
+        std::fs::create_dir_all(error_file_path.parent().unwrap()).unwrap();
         std::fs::write(error_file_path, format!("{e:#?}")).unwrap();
     }
 }
@@ -0,0 +1,266 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::project_dirs;
+
+const MANIFEST_FILE_NAME: &str = "install-manifest.toml";
+
+/// Hashes a file with SHA-256.
+///
+/// # Returns
+///
+/// The hash in lowercase hex.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Checks whether two paths name the same file, resolving symlinks and `..` if both exist.
+fn is_same_path(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (fs::canonicalize(a), fs::canonicalize(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// How an installed file compares to what was installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Unchanged,
+    Modified,
+    Missing,
+}
+
+impl fmt::Display for FileStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // pad() so callers can align the status in columns
+        f.pad(match self {
+            Self::Unchanged => "ok",
+            Self::Modified => "modified",
+            Self::Missing => "missing",
+        })
+    }
+}
+
+/// A file written by the installer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledFile {
+    pub path: PathBuf,
+    /// SHA-256 of the file as installed, in lowercase hex.
+    pub sha256: String,
+}
+
+impl InstalledFile {
+    /// Records a file as it is now.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read.
+    pub fn hash(path: &Path) -> anyhow::Result<Self> {
+        let sha256 =
+            sha256_file(path).with_context(|| format!("Failed to hash {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            sha256,
+        })
+    }
+
+    /// Compares the file on disk to the recorded hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read.
+    pub fn status(&self) -> io::Result<FileStatus> {
+        match sha256_file(&self.path) {
+            Ok(sha256) if sha256 == self.sha256 => Ok(FileStatus::Unchanged),
+            Ok(_) => Ok(FileStatus::Modified),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(FileStatus::Missing),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// What [`InstallManifest::remove_files`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemovalReport {
+    pub removed: Vec<PathBuf>,
+    /// Files changed since they were installed. They are left alone.
+    pub kept_modified: Vec<PathBuf>,
+    /// Files that were already gone.
+    pub missing: Vec<PathBuf>,
+}
+
+/// Everything the installer wrote, kept in the local data directory so that re-installing,
+/// upgrading and uninstalling know what is there.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallManifest {
+    /// The installed version.
+    pub version: Version,
+    pub installed_at: DateTime<Utc>,
+    /// Where spotikill was installed. Shortcuts and the like live elsewhere.
+    pub install_dir: PathBuf,
+    pub files: Vec<InstalledFile>,
+}
+
+impl InstallManifest {
+    /// Records an install of this version that just happened.
+    ///
+    /// # Arguments
+    ///
+    /// * `install_dir` - Where spotikill was installed.
+    /// * `files` - Every file the installer wrote. They are hashed as they are now.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file can't be read.
+    pub fn new(install_dir: &Path, files: &[PathBuf]) -> anyhow::Result<Self> {
+        Ok(Self {
+            version: crate::update::current_version(),
+            installed_at: Utc::now(),
+            install_dir: install_dir.to_path_buf(),
+            files: files
+                .iter()
+                .map(|path| InstalledFile::hash(path))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+
+    /// Gets the path to the manifest of the current install. The file may not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined.
+    pub fn path() -> anyhow::Result<PathBuf> {
+        Ok(project_dirs()?.data_local_dir().join(MANIFEST_FILE_NAME))
+    }
+
+    /// Loads the manifest of the current install, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest exists but cannot be read or parsed.
+    pub fn load() -> anyhow::Result<Option<Self>> {
+        Self::read(&Self::path()?)
+    }
+
+    /// Saves this as the manifest of the current install.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest can't be written.
+    pub fn save(&self) -> anyhow::Result<PathBuf> {
+        let path = Self::path()?;
+        self.write(&path)?;
+        Ok(path)
+    }
+
+    /// Reads a manifest, or `None` if there isn't one at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn read(path: &Path) -> anyhow::Result<Option<Self>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        toml::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Writes the manifest to `path`, creating its directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest can't be serialized or written.
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let contents = toml::to_string_pretty(self).context("Failed to serialize the manifest")?;
+        fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Gets the recorded file at `path`, if there is one.
+    pub fn file(&self, path: &Path) -> Option<&InstalledFile> {
+        self.files
+            .iter()
+            .find(|file| is_same_path(&file.path, path))
+    }
+
+    /// Lists the files that were changed since they were installed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file exists but can't be read.
+    pub fn modified_files(&self) -> io::Result<Vec<&InstalledFile>> {
+        let mut modified = Vec::new();
+        for file in &self.files {
+            if file.status()? == FileStatus::Modified {
+                modified.push(file);
+            }
+        }
+        Ok(modified)
+    }
+
+    /// Deletes the recorded files, then the directories in the install directory that this left
+    /// empty. Files changed since they were installed are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep` - Files not to delete, e.g. the ones a new install still uses.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file can't be read or deleted.
+    pub fn remove_files(&self, keep: &[PathBuf]) -> anyhow::Result<RemovalReport> {
+        let mut report = RemovalReport::default();
+        for file in &self.files {
+            if keep.iter().any(|path| is_same_path(path, &file.path)) {
+                continue;
+            }
+            let status = file
+                .status()
+                .with_context(|| format!("Failed to read {}", file.path.display()))?;
+            match status {
+                FileStatus::Unchanged => {
+                    fs::remove_file(&file.path)
+                        .with_context(|| format!("Failed to delete {}", file.path.display()))?;
+                    self.remove_empty_parents(&file.path);
+                    report.removed.push(file.path.clone());
+                }
+                FileStatus::Modified => report.kept_modified.push(file.path.clone()),
+                FileStatus::Missing => report.missing.push(file.path.clone()),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Deletes the parents of a deleted file up to and including the install directory, stopping
+    /// at the first one that isn't empty.
+    fn remove_empty_parents(&self, path: &Path) {
+        for dir in path.ancestors().skip(1) {
+            if !dir.starts_with(&self.install_dir) || fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::Path;
+
+use sysinfo::Process;
+
+use crate::config::Config;
+
+/// An operating system, as far as matching Spotify processes is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    Windows,
+    MacOs,
+    Linux,
+}
+
+impl Platform {
+    /// Gets the platform this was compiled for. Other Unixes are treated like Linux.
+    pub const fn current() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else {
+            Self::Linux
+        }
+    }
+
+    /// Gets the names of every process the official Spotify client runs on this platform.
+    pub const fn official_process_names(self) -> &'static [&'static str] {
+        match self {
+            Self::Windows => &["Spotify.exe", "SpotifyWebHelper.exe", "SpotifyMigrator.exe"],
+            Self::MacOs => &[
+                "Spotify",
+                "Spotify Helper",
+                "Spotify Helper (GPU)",
+                "Spotify Helper (Renderer)",
+                "Spotify Helper (Plugin)",
+                // inside the Chromium Embedded Framework in Spotify.app
+                "crashpad_handler",
+                "SpotifyMigrator",
+                "SpotifyWebHelper",
+            ],
+            // every process, including the Chromium children, is called "spotify"
+            Self::Linux => &["spotify"],
+        }
+    }
+
+    /// Whether process and file names are case-insensitive.
+    const fn ignores_case(self) -> bool {
+        matches!(self, Self::Windows)
+    }
+
+    /// Whether `c` separates path components. Paths are split by hand rather than with
+    /// [`Path`] so that every platform can be matched from any other.
+    const fn is_separator(self, c: char) -> bool {
+        match self {
+            Self::Windows => matches!(c, '\\' | '/'),
+            Self::MacOs | Self::Linux => c == '/',
+        }
+    }
+
+    fn names_equal(self, a: &str, b: &str) -> bool {
+        if self.ignores_case() {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    }
+}
+
+/// Decides which processes belong to the official Spotify client.
+///
+/// A process matches if its name is one of [`Platform::official_process_names`], its executable
+/// has the same name and lives inside a directory with "spotify" in its name (e.g.
+/// `%APPDATA%\Spotify` or `Spotify.app`), and it isn't excluded by the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessMatcher {
+    platform: Platform,
+    exclude: Vec<String>,
+}
+
+impl Default for ProcessMatcher {
+    fn default() -> Self {
+        Self::new(Platform::current(), Vec::new())
+    }
+}
+
+impl ProcessMatcher {
+    /// Creates a matcher for the given platform.
+    ///
+    /// # Arguments
+    ///
+    /// * `platform` - The platform whose process names to match.
+    /// * `exclude` - Process names or full executable paths that never match.
+    pub fn new(platform: Platform, exclude: Vec<String>) -> Self {
+        Self { platform, exclude }
+    }
+
+    /// Creates a matcher for the current platform using the exclude list from the config.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(Platform::current(), config.exclude_processes.clone())
+    }
+
+    /// Checks whether a process with the given name and executable is part of Spotify.
+    pub fn matches(&self, name: &str, exe: Option<&Path>) -> bool {
+        let platform = self.platform;
+        let is_official_name = |name: &str| {
+            platform
+                .official_process_names()
+                .iter()
+                .any(|official| platform.names_equal(official, name))
+        };
+        if !is_official_name(name) {
+            return false;
+        }
+
+        // the name alone is easy to fake, so make sure the executable agrees
+        let Some(exe) = exe else {
+            return false;
+        };
+        let exe_path = exe.to_string_lossy();
+        let mut components = exe_path
+            .rsplit(|c| platform.is_separator(c))
+            .filter(|component| !component.is_empty());
+        let exe_name = components.next().unwrap_or_default();
+        if !platform.names_equal(exe_name, name) {
+            return false;
+        }
+        let in_spotify_dir = components.any(|dir| dir.to_lowercase().contains("spotify"));
+        if !in_spotify_dir {
+            return false;
+        }
+
+        !self.exclude.iter().any(|excluded| {
+            platform.names_equal(excluded, name) || platform.names_equal(excluded, &exe_path)
+        })
+    }
+
+    /// Checks whether a running process is part of Spotify.
+    pub fn matches_process(&self, proc: &Process) -> bool {
+        self.matches(proc.name(), proc.exe())
+    }
+}
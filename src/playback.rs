@@ -0,0 +1,286 @@
+#[cfg(unix)]
+use std::process::Command;
+
+use crate::{matcher::ProcessMatcher, process::find_spotify_pids};
+
+/// Whether Spotify is currently playing something.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackState {
+    Playing,
+    /// Paused or stopped.
+    Paused,
+    /// Spotify isn't running or its state couldn't be determined.
+    Unknown,
+}
+
+impl PlaybackState {
+    /// Returns `self`, or asks `fallback` if this is [`PlaybackState::Unknown`].
+    #[must_use]
+    pub fn or_else<F>(self, fallback: F) -> Self
+    where
+        F: FnOnce() -> Self,
+    {
+        match self {
+            Self::Unknown => fallback(),
+            state => state,
+        }
+    }
+}
+
+/// Tells whether Spotify is playing. The kill and schedule policies only ask through this, so
+/// tests can fake it.
+pub trait PlaybackDetector {
+    fn playback_state(&self) -> PlaybackState;
+}
+
+/// Detects playback on this system.
+///
+/// An active audio stream from any Spotify process counts first, since that still works while
+/// Spotify's UI is frozen. If there is no stream to go by, Spotify itself is asked: over MPRIS on
+/// Linux, with AppleScript on macOS and from the window title on Windows.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemPlayback<'a> {
+    matcher: &'a ProcessMatcher,
+}
+
+impl<'a> SystemPlayback<'a> {
+    /// # Arguments
+    ///
+    /// * `matcher` - Decides which processes belong to Spotify.
+    pub const fn new(matcher: &'a ProcessMatcher) -> Self {
+        Self { matcher }
+    }
+}
+
+impl PlaybackDetector for SystemPlayback<'_> {
+    fn playback_state(&self) -> PlaybackState {
+        let pids: Vec<_> = find_spotify_pids(self.matcher)
+            .iter()
+            .map(|pid| pid.as_u32())
+            .collect();
+        if pids.is_empty() {
+            return PlaybackState::Unknown;
+        }
+        audio_stream_state(&pids).or_else(|| player_state(&pids))
+    }
+}
+
+/// Reads the output of `pactl list sink-inputs` (which PipeWire understands too) in the C locale.
+///
+/// # Arguments
+///
+/// * `output` - What `pactl` printed.
+/// * `pids` - The Spotify process IDs.
+///
+/// # Returns
+///
+/// [`PlaybackState::Playing`] if any Spotify stream is running, [`PlaybackState::Paused`] if
+/// they are all corked and [`PlaybackState::Unknown`] if Spotify has none.
+pub fn sink_input_state(output: &str, pids: &[u32]) -> PlaybackState {
+    let mut state = PlaybackState::Unknown;
+    for sink_input in output.split("Sink Input #").skip(1) {
+        let mut corked = None;
+        let mut pid = None;
+        for line in sink_input.lines().map(str::trim) {
+            if let Some(value) = line.strip_prefix("Corked:") {
+                corked = Some(value.trim() == "yes");
+            } else if let Some(value) = line.strip_prefix("application.process.id = ") {
+                pid = value.trim_matches('"').parse::<u32>().ok();
+            }
+        }
+        if !pid.is_some_and(|pid| pids.contains(&pid)) {
+            continue;
+        }
+        match corked {
+            Some(false) => return PlaybackState::Playing,
+            Some(true) => state = PlaybackState::Paused,
+            None => {}
+        }
+    }
+    state
+}
+
+/// Reads the output of `pmset -g assertions`. `coreaudiod` keeps the system awake on behalf of
+/// every process that is playing audio, and names that process.
+///
+/// # Arguments
+///
+/// * `output` - What `pmset` printed.
+/// * `pids` - The Spotify process IDs.
+///
+/// # Returns
+///
+/// [`PlaybackState::Playing`] if audio is playing for any Spotify process, otherwise
+/// [`PlaybackState::Unknown`]: no assertion doesn't prove that Spotify is paused.
+pub fn audio_assertion_state(output: &str, pids: &[u32]) -> PlaybackState {
+    let mut in_audio_assertion = false;
+    for line in output.lines().map(str::trim) {
+        if let Some(pid) = line.strip_prefix("Created for PID:") {
+            let pid = pid.trim().trim_end_matches('.').parse::<u32>().ok();
+            if in_audio_assertion && pid.is_some_and(|pid| pids.contains(&pid)) {
+                return PlaybackState::Playing;
+            }
+        }
+        in_audio_assertion = line.contains("named: \"com.apple.audio.");
+    }
+    PlaybackState::Unknown
+}
+
+/// Runs a command and returns its trimmed stdout if it succeeded.
+#[cfg(unix)]
+pub(crate) fn command_output(command: &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn audio_stream_state(pids: &[u32]) -> PlaybackState {
+    command_output(
+        Command::new("pactl")
+            .args(["list", "sink-inputs"])
+            .env("LC_ALL", "C"),
+    )
+    .map_or(PlaybackState::Unknown, |output| {
+        sink_input_state(&output, pids)
+    })
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn player_state(_pids: &[u32]) -> PlaybackState {
+    // SEE: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html
+    let Some(reply) = command_output(Command::new("dbus-send").args([
+        "--print-reply",
+        "--dest=org.mpris.MediaPlayer2.spotify",
+        "/org/mpris/MediaPlayer2",
+        "org.freedesktop.DBus.Properties.Get",
+        "string:org.mpris.MediaPlayer2.Player",
+        "string:PlaybackStatus",
+    ])) else {
+        return PlaybackState::Unknown;
+    };
+
+    if reply.contains("\"Playing\"") {
+        PlaybackState::Playing
+    } else if reply.contains("\"Paused\"") || reply.contains("\"Stopped\"") {
+        PlaybackState::Paused
+    } else {
+        PlaybackState::Unknown
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn audio_stream_state(pids: &[u32]) -> PlaybackState {
+    command_output(Command::new("pmset").args(["-g", "assertions"]))
+        .map_or(PlaybackState::Unknown, |output| {
+            audio_assertion_state(&output, pids)
+        })
+}
+
+#[cfg(target_os = "macos")]
+fn player_state(_pids: &[u32]) -> PlaybackState {
+    const SCRIPT: &str = r#"if application "Spotify" is running then tell application "Spotify" to player state as string"#;
+
+    match command_output(Command::new("osascript").args(["-e", SCRIPT])).as_deref() {
+        Some("playing") => PlaybackState::Playing,
+        Some("paused" | "stopped") => PlaybackState::Paused,
+        _ => PlaybackState::Unknown,
+    }
+}
+
+/// Looks for Spotify's audio sessions on the default output device. A session is only active while
+/// it is actually playing.
+#[cfg(windows)]
+fn audio_stream_state(pids: &[u32]) -> PlaybackState {
+    use windows::core::ComInterface;
+    use windows::Win32::Media::Audio::{
+        eMultimedia, eRender, AudioSessionStateActive, IAudioSessionControl2,
+        IAudioSessionManager2, IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_APARTMENTTHREADED,
+    };
+
+    // (process ID, whether the session is active) for every session
+    let sessions = || -> windows::core::Result<Vec<(u32, bool)>> {
+        unsafe {
+            // succeeds without doing anything if the event loop already initialized COM
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            let devices: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let device = devices.GetDefaultAudioEndpoint(eRender, eMultimedia)?;
+            let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+            let sessions = manager.GetSessionEnumerator()?;
+            (0..sessions.GetCount()?)
+                .map(|i| {
+                    let session = sessions.GetSession(i)?;
+                    let active = session.GetState()? == AudioSessionStateActive;
+                    let pid = session.cast::<IAudioSessionControl2>()?.GetProcessId()?;
+                    Ok((pid, active))
+                })
+                .collect()
+        }
+    };
+
+    let Ok(sessions) = sessions() else {
+        return PlaybackState::Unknown;
+    };
+    let mut state = PlaybackState::Unknown;
+    for (pid, active) in sessions {
+        if !pids.contains(&pid) {
+            continue;
+        }
+        if active {
+            return PlaybackState::Playing;
+        }
+        state = PlaybackState::Paused;
+    }
+    state
+}
+
+/// Spotify sets its main window title to "Artist - Song" while playing and to "Spotify",
+/// "Spotify Free" or "Spotify Premium" while paused.
+#[cfg(windows)]
+fn player_state(pids: &[u32]) -> PlaybackState {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible,
+    };
+
+    struct TitleSearch<'a> {
+        pids: &'a [u32],
+        titles: Vec<String>,
+    }
+
+    unsafe extern "system" fn collect_title(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut TitleSearch);
+        let mut pid = 0;
+        GetWindowThreadProcessId(hwnd, Some(std::ptr::addr_of_mut!(pid)));
+        if search.pids.contains(&pid) && IsWindowVisible(hwnd).as_bool() {
+            let mut buffer = [0u16; 512];
+            let len = usize::try_from(GetWindowTextW(hwnd, &mut buffer)).unwrap_or_default();
+            if len > 0 {
+                search.titles.push(String::from_utf16_lossy(&buffer[..len]));
+            }
+        }
+        // keep enumerating
+        true.into()
+    }
+
+    let mut search = TitleSearch {
+        pids,
+        titles: Vec::new(),
+    };
+    let lparam = LPARAM(std::ptr::addr_of_mut!(search) as isize);
+    if unsafe { EnumWindows(Some(collect_title), lparam) }.is_err() {
+        return PlaybackState::Unknown;
+    }
+
+    match search.titles.first() {
+        Some(title) if title.starts_with("Spotify") => PlaybackState::Paused,
+        Some(title) if title.contains(" - ") => PlaybackState::Playing,
+        _ => PlaybackState::Unknown,
+    }
+}
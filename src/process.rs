@@ -0,0 +1,677 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessStatus, RefreshKind, System, UpdateKind};
+
+use crate::{
+    config::Config,
+    flavour::InstallFlavour,
+    matcher::ProcessMatcher,
+    role::ProcessRole,
+    safety::{KillPolicy, SkipReason},
+};
+
+/// How long to wait for killed processes to exit and to watch for respawned ones.
+const EXIT_DEADLINE: Duration = Duration::from_secs(5);
+/// How many times processes are killed, counting the first, before respawns are just reported.
+const MAX_KILL_ROUNDS: usize = 3;
+/// How often to check the process table while waiting for processes to exit.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long Spotify must stay gone to count as killed, so respawns have a chance to show up.
+const EXIT_SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// The least that must be refreshed to tell Spotify processes apart from the rest.
+fn matching_refresh_kind() -> ProcessRefreshKind {
+    ProcessRefreshKind::new().with_exe(UpdateKind::OnlyIfNotSet)
+}
+
+/// A snapshot of a single Spotify process taken right before it was killed.
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: Pid,
+    pub parent: Option<Pid>,
+    pub name: String,
+    pub exe: Option<PathBuf>,
+    pub flavour: InstallFlavour,
+    pub role: ProcessRole,
+    /// Resident memory in bytes.
+    pub memory: u64,
+    /// CPU usage in percent. This can exceed 100% on multi-core machines.
+    pub cpu_usage: f32,
+}
+
+impl ProcessInfo {
+    fn of(proc: &Process) -> Self {
+        Self {
+            pid: proc.pid(),
+            parent: proc.parent(),
+            name: proc.name().to_string(),
+            exe: proc.exe().map(Path::to_path_buf),
+            flavour: InstallFlavour::of_process(proc),
+            role: ProcessRole::classify(proc.name(), proc.cmd()),
+            memory: proc.memory(),
+            cpu_usage: proc.cpu_usage(),
+        }
+    }
+}
+
+/// A Spotify process that was left alone, and why.
+#[derive(Debug, Clone)]
+pub struct SkippedProcess {
+    pub info: ProcessInfo,
+    pub reason: SkipReason,
+}
+
+impl fmt::Display for SkippedProcess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.info.name, self.info.pid, self.reason)
+    }
+}
+
+/// What [`kill_spotify_processes`] would do if it ran right now.
+#[derive(Debug, Clone, Default)]
+pub struct KillPreview {
+    /// Processes that would be killed.
+    pub targets: Vec<ProcessInfo>,
+    /// Processes that would be left alone because of the [`KillPolicy`].
+    pub skipped: Vec<SkippedProcess>,
+}
+
+impl KillPreview {
+    /// Total resident memory (in bytes) of every process that would be killed.
+    pub fn memory(&self) -> u64 {
+        self.targets.iter().map(|proc| proc.memory).sum()
+    }
+
+    /// The question to ask before killing, e.g. "Kill 7 Spotify processes (1 main, 4 renderer,
+    /// 1 GPU, 1 utility) using 1.2 GB?".
+    pub fn question(&self) -> String {
+        let count = self.targets.len();
+        let plural = if count == 1 { "" } else { "es" };
+        format!(
+            "Kill {count} Spotify process{plural} ({}) using {}?",
+            count_roles(&self.targets),
+            format_bytes(self.memory())
+        )
+    }
+}
+
+impl fmt::Display for KillPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.targets.is_empty() {
+            writeln!(f, "Nothing to kill")?;
+        } else {
+            writeln!(f, "{}", self.question())?;
+        }
+        for proc in &self.targets {
+            writeln!(
+                f,
+                "  {:<13} {} ({}): {} RAM",
+                proc.role,
+                proc.name,
+                proc.pid,
+                format_bytes(proc.memory)
+            )?;
+        }
+        for skipped in &self.skipped {
+            writeln!(f, "  {:<13} {skipped}", "left alone")?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts processes by role, e.g. "1 main, 4 renderer, 1 GPU".
+fn count_roles(processes: &[ProcessInfo]) -> String {
+    let counts: Vec<_> = ProcessRole::ALL
+        .iter()
+        .filter_map(|&role| {
+            let count = processes.iter().filter(|proc| proc.role == role).count();
+            (count > 0).then(|| format!("{count} {role}"))
+        })
+        .collect();
+    counts.join(", ")
+}
+
+/// Everything that happened during a single call to [`kill_spotify_processes`].
+#[derive(Debug, Clone, Default)]
+pub struct KillReport {
+    /// Every process that was killed, including respawned ones killed in later rounds.
+    pub killed: Vec<ProcessInfo>,
+    /// Processes that were allowed to be killed but refused the signal, e.g. for lack of
+    /// permission.
+    pub failed: Vec<ProcessInfo>,
+    /// Processes that matched but weren't killed because of the [`KillPolicy`].
+    pub skipped: Vec<SkippedProcess>,
+    /// Killed processes that still hadn't exited when the deadline ran out.
+    pub lingering: Vec<ProcessInfo>,
+    /// New Spotify processes that were still running when the deadline ran out, e.g. restarted by
+    /// Spotify's updater or crash handler.
+    pub respawned: Vec<ProcessInfo>,
+}
+
+impl KillReport {
+    /// Total resident memory (in bytes) of every killed process.
+    pub fn memory_freed(&self) -> u64 {
+        self.killed.iter().map(|proc| proc.memory).sum()
+    }
+
+    /// Total CPU usage (in percent) of every killed process.
+    pub fn cpu_freed(&self) -> f32 {
+        self.killed.iter().map(|proc| proc.cpu_usage).sum()
+    }
+
+    /// Gets the main Spotify process: the killed process with the main role or, failing that,
+    /// the killed process whose parent was not killed as well.
+    pub fn main_process(&self) -> Option<&ProcessInfo> {
+        self.killed
+            .iter()
+            .find(|proc| proc.role == ProcessRole::Main)
+            .or_else(|| {
+                self.killed.iter().find(|proc| match proc.parent {
+                    Some(parent) => self.killed.iter().all(|p| p.pid != parent),
+                    None => true,
+                })
+            })
+    }
+
+    /// A one-line summary, e.g. "Freed 1.8 GB RAM and 12.5% CPU across 7 processes, skipped 1".
+    pub fn summary(&self) -> String {
+        let count = self.killed.len();
+        let plural = if count == 1 { "" } else { "es" };
+        let mut summary = format!(
+            "Freed {} RAM and {:.1}% CPU across {count} process{plural}",
+            format_bytes(self.memory_freed()),
+            self.cpu_freed()
+        );
+        if !self.failed.is_empty() {
+            summary.push_str(&format!(", failed to kill {}", self.failed.len()));
+        }
+        if !self.skipped.is_empty() {
+            summary.push_str(&format!(", skipped {}", self.skipped.len()));
+        }
+        if !self.lingering.is_empty() {
+            summary.push_str(&format!(", {} still exiting", self.lingering.len()));
+        }
+        if !self.respawned.is_empty() {
+            summary.push_str(", respawned processes detected");
+        }
+        summary
+    }
+}
+
+impl fmt::Display for KillReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Kill report: {}", self.summary())?;
+        for proc in &self.killed {
+            writeln!(
+                f,
+                "  killed {} ({}, {}, {}): {} RAM, {:.1}% CPU",
+                proc.name,
+                proc.pid,
+                proc.flavour,
+                proc.role,
+                format_bytes(proc.memory),
+                proc.cpu_usage
+            )?;
+        }
+        for proc in &self.failed {
+            writeln!(f, "  failed to kill {} ({})", proc.name, proc.pid)?;
+        }
+        for skipped in &self.skipped {
+            writeln!(f, "  skipped {skipped}")?;
+        }
+        for proc in &self.lingering {
+            writeln!(f, "  still exiting {} ({})", proc.name, proc.pid)?;
+        }
+        for proc in &self.respawned {
+            writeln!(f, "  respawned {} ({})", proc.name, proc.pid)?;
+        }
+        Ok(())
+    }
+}
+
+/// Spotify's combined resource usage at a point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpotifyStatus {
+    pub process_count: usize,
+    /// Resident memory in bytes.
+    pub memory: u64,
+    /// CPU usage in percent. This can exceed 100% on multi-core machines.
+    pub cpu_usage: f32,
+    /// Number of processes that are stopped or defunct, or whose window is hung on Windows.
+    pub unresponsive_count: usize,
+}
+
+/// How Spotify is doing overall, as shown by the tray icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpotifyHealth {
+    NotRunning,
+    Healthy,
+    /// Using excessive memory or CPU, or some process appears hung.
+    Unhealthy,
+}
+
+impl SpotifyStatus {
+    pub fn is_running(&self) -> bool {
+        self.process_count > 0
+    }
+
+    /// Judges the status against the warning thresholds in the config.
+    pub fn health(&self, config: &Config) -> SpotifyHealth {
+        if !self.is_running() {
+            return SpotifyHealth::NotRunning;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let over_cpu = self.cpu_usage > config.cpu_warning_percent as f32;
+        let over_memory = self.memory > config.memory_warning_mb.saturating_mul(1024 * 1024);
+        if over_cpu || over_memory || self.unresponsive_count > 0 {
+            SpotifyHealth::Unhealthy
+        } else {
+            SpotifyHealth::Healthy
+        }
+    }
+}
+
+impl fmt::Display for SpotifyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_running() {
+            return write!(f, "Spotify is not running");
+        }
+        let plural = if self.process_count == 1 { "" } else { "es" };
+        write!(
+            f,
+            "Spotify: {} process{plural}, {} RAM, {:.1}% CPU",
+            self.process_count,
+            format_bytes(self.memory),
+            self.cpu_usage
+        )?;
+        if self.unresponsive_count > 0 {
+            write!(f, ", {} not responding", self.unresponsive_count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps a process table around between refreshes so CPU usage can be measured without blocking.
+pub struct SpotifyMonitor {
+    system: System,
+    matcher: ProcessMatcher,
+}
+
+impl SpotifyMonitor {
+    pub fn new(matcher: ProcessMatcher) -> Self {
+        Self {
+            system: System::new(),
+            matcher,
+        }
+    }
+
+    /// Refreshes the process table and sums up the usage of every Spotify process.
+    ///
+    /// CPU usage is measured since the previous refresh, so it is always 0% the first time.
+    pub fn refresh(&mut self) -> SpotifyStatus {
+        self.system
+            .refresh_processes_specifics(matching_refresh_kind().with_memory().with_cpu());
+
+        let spotify: Vec<_> = self
+            .system
+            .processes()
+            .values()
+            .filter(|proc| self.matcher.matches_process(proc))
+            .collect();
+        let status = spotify
+            .iter()
+            .fold(SpotifyStatus::default(), |status, proc| {
+                let unresponsive = matches!(
+                    proc.status(),
+                    ProcessStatus::Stop | ProcessStatus::Zombie | ProcessStatus::Dead
+                );
+                SpotifyStatus {
+                    process_count: status.process_count + 1,
+                    memory: status.memory + proc.memory(),
+                    cpu_usage: status.cpu_usage + proc.cpu_usage(),
+                    unresponsive_count: status.unresponsive_count + usize::from(unresponsive),
+                }
+            });
+        SpotifyStatus {
+            unresponsive_count: status.unresponsive_count + hung_window_count(&spotify),
+            ..status
+        }
+    }
+}
+
+/// Counts the hung windows of the given Spotify processes. sysinfo never reports a process as
+/// stopped on Windows, but Windows knows when a window stops responding.
+#[cfg(windows)]
+fn hung_window_count(spotify: &[&Process]) -> usize {
+    let pids: Vec<_> = spotify.iter().map(|proc| proc.pid().as_u32()).collect();
+    usize::from(crate::window::main_window_hung(&pids))
+}
+
+#[cfg(not(windows))]
+const fn hung_window_count(_spotify: &[&Process]) -> usize {
+    0
+}
+
+/// Formats a byte count as a human-readable string using binary units, e.g. `1.8 GB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    #[allow(clippy::cast_precision_loss)]
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Finds the PIDs of every running Spotify process.
+pub fn find_spotify_pids(matcher: &ProcessMatcher) -> Vec<Pid> {
+    let s = System::new_with_specifics(RefreshKind::new().with_processes(matching_refresh_kind()));
+    s.processes()
+        .values()
+        .filter(|proc| matcher.matches_process(proc))
+        .map(Process::pid)
+        .collect()
+}
+
+/// Finds every running Spotify process with just enough refreshed to apply the kill policy.
+/// Processes that have exited but haven't been reaped yet don't count.
+fn list_spotify_processes(matcher: &ProcessMatcher) -> (System, Vec<Pid>) {
+    // users are needed for every process, spotikill included, to apply the kill policy
+    let s = System::new_with_specifics(
+        RefreshKind::new().with_processes(
+            matching_refresh_kind()
+                .with_user(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_memory(),
+        ),
+    );
+
+    let spotify_pids: Vec<_> = s
+        .processes()
+        .values()
+        .filter(|proc| {
+            matcher.matches_process(proc)
+                && !matches!(proc.status(), ProcessStatus::Zombie | ProcessStatus::Dead)
+        })
+        .map(sysinfo::Process::pid)
+        .collect();
+    (s, spotify_pids)
+}
+
+/// Finds every running Spotify process and refreshes its memory and CPU usage.
+///
+/// CPU usage is computed from the difference between two refreshes, so this blocks for
+/// [`sysinfo::MINIMUM_CPU_UPDATE_INTERVAL`].
+fn scan_spotify_processes(matcher: &ProcessMatcher) -> (System, Vec<Pid>) {
+    let (mut s, spotify_pids) = list_spotify_processes(matcher);
+
+    let usage_refresh = matching_refresh_kind()
+        .with_memory()
+        .with_cpu()
+        .with_cmd(UpdateKind::OnlyIfNotSet);
+    for &pid in &spotify_pids {
+        s.refresh_process_specifics(pid, usage_refresh);
+    }
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    for &pid in &spotify_pids {
+        s.refresh_process_specifics(pid, usage_refresh);
+    }
+
+    (s, spotify_pids)
+}
+
+/// Sorts the scanned processes into those the policy allows to kill and those it doesn't.
+fn plan_kill(s: &System, spotify_pids: &[Pid], policy: KillPolicy) -> KillPreview {
+    let mut preview = KillPreview::default();
+    // processes may exit between the scan and now, those are skipped silently
+    for proc in spotify_pids.iter().filter_map(|pid| s.process(*pid)) {
+        let info = ProcessInfo::of(proc);
+        match policy.check(proc, s) {
+            Some(reason) => preview.skipped.push(SkippedProcess { info, reason }),
+            None => preview.targets.push(info),
+        }
+    }
+    preview
+}
+
+/// Finds out what [`kill_spotify_processes`] would do without killing anything.
+///
+/// This blocks for [`sysinfo::MINIMUM_CPU_UPDATE_INTERVAL`] to measure CPU usage.
+pub fn preview_kill(matcher: &ProcessMatcher, policy: KillPolicy) -> KillPreview {
+    let (s, spotify_pids) = scan_spotify_processes(matcher);
+    plan_kill(&s, &spotify_pids, policy)
+}
+
+/// Kills every running Spotify process the policy allows. Sandboxed installs are killed through
+/// their sandbox so the wrapper processes go down with Spotify.
+///
+/// # Arguments
+///
+/// * `matcher` - Decides which processes belong to Spotify.
+/// * `policy` - Decides which of those may be killed. The rest are listed in the report.
+///
+/// # Errors
+///
+/// Returns an error if no Spotify processes are running, if the policy forbids killing every one
+/// of them, if none of them could be signalled, or if a sandbox fails to kill Spotify.
+pub fn kill_spotify_processes(
+    matcher: &ProcessMatcher,
+    policy: KillPolicy,
+) -> anyhow::Result<KillReport> {
+    let (s, spotify_pids) = scan_spotify_processes(matcher);
+
+    anyhow::ensure!(!spotify_pids.is_empty(), "No Spotify processes found");
+
+    let KillPreview { targets, skipped } = plan_kill(&s, &spotify_pids, policy);
+    if targets.is_empty() {
+        let skipped: Vec<_> = skipped.iter().map(ToString::to_string).collect();
+        anyhow::bail!(
+            "Not allowed to kill any Spotify process: {}",
+            skipped.join("; ")
+        );
+    }
+
+    let mut report = KillReport {
+        skipped,
+        ..KillReport::default()
+    };
+    kill_targets(&s, targets, &mut report)?;
+    anyhow::ensure!(
+        !report.killed.is_empty(),
+        "Failed to kill any Spotify process"
+    );
+    verify_exit(matcher, policy, &spotify_pids, &mut report)?;
+    Ok(report)
+}
+
+/// Kills the given processes, through their sandbox where there is one, and adds them to
+/// [`KillReport::killed`] or, if they refused the signal, [`KillReport::failed`]. A sandbox is only
+/// killed as a whole if none of the [`KillReport::skipped`] processes run in it.
+fn kill_targets(
+    s: &System,
+    targets: Vec<ProcessInfo>,
+    report: &mut KillReport,
+) -> anyhow::Result<()> {
+    let spared = &report.skipped;
+    let sandbox_kill = |flavour: InstallFlavour| {
+        flavour
+            .kill_command()
+            .filter(|_| spared.iter().all(|skipped| skipped.info.flavour != flavour))
+    };
+    let flavours: HashSet<_> = targets.iter().map(|proc| proc.flavour).collect();
+    for mut command in flavours.into_iter().filter_map(sandbox_kill) {
+        #[cfg(debug_assertions)]
+        println!("Killing sandboxed Spotify: {command:?}");
+
+        let status = command
+            .status()
+            .with_context(|| format!("Failed to run {command:?}"))?;
+        anyhow::ensure!(status.success(), "{command:?} failed: {status}");
+    }
+
+    let (killed, failed): (Vec<_>, Vec<_>) = targets.into_iter().partition(|info| {
+        // processes that exited on their own in the meantime are gone all the same
+        let Some(proc) = s.process(info.pid) else {
+            return true;
+        };
+        if sandbox_kill(info.flavour).is_some() {
+            return true;
+        }
+        #[cfg(debug_assertions)]
+        println!("Killing process: {} ({})", proc.name(), proc.pid());
+
+        proc.kill()
+    });
+    report.killed.extend(killed);
+    report.failed.extend(failed);
+    Ok(())
+}
+
+/// Waits for the killed processes to exit, re-scanning the process table until they have been gone
+/// for [`EXIT_SETTLE_TIME`] or [`EXIT_DEADLINE`] runs out. Spotify's updater or crash handler may
+/// start new processes in the meantime; those are killed too, for up to [`MAX_KILL_ROUNDS`] rounds
+/// in total, and anything still running at the deadline ends up in [`KillReport::lingering`] or
+/// [`KillReport::respawned`].
+///
+/// # Arguments
+///
+/// * `matcher` - Decides which processes belong to Spotify.
+/// * `policy` - Decides which new processes may be killed. The rest are added to the report as
+///   skipped.
+/// * `seen` - Every Spotify process found before killing, whether it was killed or not.
+/// * `report` - The report of the first round, which is updated.
+///
+/// # Errors
+///
+/// Returns an error if a sandbox fails to kill respawned processes.
+fn verify_exit(
+    matcher: &ProcessMatcher,
+    policy: KillPolicy,
+    seen: &[Pid],
+    report: &mut KillReport,
+) -> anyhow::Result<()> {
+    let deadline = Instant::now() + EXIT_DEADLINE;
+    let mut seen: HashSet<_> = seen.iter().copied().collect();
+    let mut rounds = 1;
+    let mut gone_since = None;
+    loop {
+        let (s, spotify_pids) = list_spotify_processes(matcher);
+        let new_pids: Vec<_> = spotify_pids
+            .iter()
+            .copied()
+            .filter(|pid| !seen.contains(pid))
+            .collect();
+        let respawned = plan_kill(&s, &new_pids, policy);
+        seen.extend(&new_pids);
+        report.skipped.extend(respawned.skipped);
+
+        let lingering: Vec<_> = report
+            .killed
+            .iter()
+            .filter(|proc| spotify_pids.contains(&proc.pid))
+            .cloned()
+            .collect();
+        let mut respawned = respawned.targets;
+        // respawned processes that were left to run are still running, whether they are new or not
+        respawned.extend(
+            report
+                .respawned
+                .drain(..)
+                .filter(|proc| spotify_pids.contains(&proc.pid)),
+        );
+
+        if lingering.is_empty() && respawned.is_empty() {
+            let gone_since = *gone_since.get_or_insert_with(Instant::now);
+            if gone_since.elapsed() >= EXIT_SETTLE_TIME || Instant::now() >= deadline {
+                return Ok(());
+            }
+            thread::sleep(EXIT_POLL_INTERVAL);
+            continue;
+        }
+        gone_since = None;
+        if Instant::now() >= deadline {
+            report.lingering = lingering;
+            report.respawned = respawned;
+            return Ok(());
+        }
+
+        if rounds < MAX_KILL_ROUNDS && !respawned.is_empty() {
+            rounds += 1;
+            kill_targets(&s, respawned, report)?;
+        } else {
+            report.respawned = respawned;
+        }
+        thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+/// Launches Spotify, detached from this process.
+///
+/// # Arguments
+///
+/// * `flavour` - How Spotify was installed.
+/// * `exe` - The Spotify executable to run, if known.
+///
+/// # Errors
+///
+/// Returns an error if Spotify fails to start.
+pub fn launch_spotify(flavour: InstallFlavour, exe: Option<&Path>) -> anyhow::Result<()> {
+    let mut command = flavour.launch_command(exe);
+
+    #[cfg(debug_assertions)]
+    println!("Launching Spotify: {command:?}");
+
+    let mut child = command.spawn().context("Failed to launch Spotify")?;
+    // reap the child whenever it exits so it doesn't linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Kills every running Spotify process and starts it again the same way it was installed.
+///
+/// # Arguments
+///
+/// * `matcher` - Decides which processes belong to Spotify.
+/// * `policy` - Decides which of those may be killed.
+///
+/// # Errors
+///
+/// Returns an error if the main process may not or could not be killed, if no Spotify processes
+/// can be killed or if Spotify fails to start.
+pub fn restart_spotify(matcher: &ProcessMatcher, policy: KillPolicy) -> anyhow::Result<KillReport> {
+    // a second client would only hand over to the first one and exit
+    anyhow::ensure!(
+        policy.kill_roles.contains(ProcessRole::Main),
+        "Spotify can't be restarted while kill_roles leaves its main process alone"
+    );
+    let report = kill_spotify_processes(matcher, policy)?;
+    anyhow::ensure!(
+        report
+            .failed
+            .iter()
+            .all(|proc| proc.role != ProcessRole::Main),
+        "Spotify can't be restarted because its main process couldn't be killed"
+    );
+    let main_process = report.main_process();
+    launch_spotify(
+        main_process.map_or_else(InstallFlavour::default, |proc| proc.flavour),
+        main_process.and_then(|proc| proc.exe.as_deref()),
+    )?;
+    Ok(report)
+}
@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+/// Name of the directory next to the executable that holds its resources.
+pub const RESOURCE_DIR_NAME: &str = "resources";
+
+#[cfg(windows)]
+const ICON_EXT: &str = "ico";
+#[cfg(unix)]
+const ICON_EXT: &str = "png";
+
+/// File name of the app icon in the format this platform's tray and notifications want.
+pub const ICON_FILE_NAME: &str = const_format::concatcp!("app-icon.", ICON_EXT);
+
+/// A resource file held in memory, e.g. one compiled into the executable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resource<'a> {
+    pub file_name: &'a str,
+    pub contents: &'a [u8],
+}
+
+/// The PNG app icon generated by `build.rs`, compiled in so the tray still has an icon when the
+/// resource directory is missing.
+pub const APP_ICON_PNG: &[u8] =
+    include_bytes!(concat!(env!("SPOTIKILL_RESOURCE_DIR"), "/app-icon.png"));
+
+/// Finds the resource directory of an executable, if it has one. This is either `resources` next
+/// to the executable, or `Contents/Resources` if it lives in a macOS app bundle.
+///
+/// # Arguments
+///
+/// * `exe` - The path to the executable.
+pub fn resource_dir_for(exe: &Path) -> Option<PathBuf> {
+    let exe_dir = exe.parent()?;
+    let next_to_exe = exe_dir.join(RESOURCE_DIR_NAME);
+    if next_to_exe.is_dir() {
+        return Some(next_to_exe);
+    }
+
+    // Spotikill.app/Contents/MacOS/spotikill -> Spotikill.app/Contents/Resources
+    let in_bundle = exe_dir.parent()?.join("Resources");
+    (exe_dir.file_name()? == "MacOS" && in_bundle.is_dir()).then_some(in_bundle)
+}
+
+/// Finds the resource directory of the running executable.
+///
+/// Debug builds fall back to the icons generated by `build.rs`, so `cargo run` works without
+/// installing.
+///
+/// # Errors
+///
+/// Returns an error if the executable has no resource directory.
+pub fn resource_dir() -> anyhow::Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to get the path to spotikill")?;
+    if let Some(dir) = resource_dir_for(&exe) {
+        return Ok(dir);
+    }
+
+    #[cfg(debug_assertions)]
+    {
+        let build_dir = Path::new(crate::constants::BUILD_RESOURCE_DIR);
+        if build_dir.is_dir() {
+            return Ok(build_dir.to_owned());
+        }
+    }
+
+    anyhow::bail!(
+        "Couldn't find the resources next to {}, try reinstalling",
+        exe.display()
+    )
+}
+
+/// Gets the path to the app icon.
+///
+/// # Errors
+///
+/// Returns an error if the resource directory can't be found.
+pub fn icon_path() -> anyhow::Result<PathBuf> {
+    Ok(resource_dir()?.join(ICON_FILE_NAME))
+}
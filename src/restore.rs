@@ -0,0 +1,354 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(unix)]
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(unix)]
+use crate::playback::command_output;
+#[cfg(windows)]
+use crate::window::main_window;
+use crate::{
+    matcher::ProcessMatcher,
+    playback::{PlaybackDetector, PlaybackState, SystemPlayback},
+    process::find_spotify_pids,
+};
+
+/// How often to check whether Spotify is back.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The `[after_restart]` table in the config file: what to put back once "Restart Spotify" has
+/// started Spotify again. Nothing is done unless `restore_window` or `resume_playback` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AfterRestart {
+    /// Whether to minimize or maximize Spotify's window again if it was before the restart.
+    pub restore_window: bool,
+    /// Whether to start playing again if Spotify was playing before the restart.
+    pub resume_playback: bool,
+    /// How long (in seconds) to wait for Spotify to respond before giving up.
+    pub timeout_secs: u64,
+}
+
+impl Default for AfterRestart {
+    fn default() -> Self {
+        Self {
+            restore_window: false,
+            resume_playback: false,
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl AfterRestart {
+    pub fn is_enabled(&self) -> bool {
+        self.restore_window || self.resume_playback
+    }
+
+    /// How long to wait for Spotify to respond. Clamped to between a second and an hour.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.clamp(1, 60 * 60))
+    }
+}
+
+/// Whether Spotify's main window is minimized, maximized or neither.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowState {
+    Normal,
+    Minimized,
+    Maximized,
+}
+
+/// What Spotify looked like right before a restart, as far as [`AfterRestart`] cares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpotifySnapshot {
+    /// `None` if the window wasn't looked at or couldn't be found.
+    pub window: Option<WindowState>,
+    pub playback: PlaybackState,
+}
+
+impl SpotifySnapshot {
+    /// Takes a snapshot of whatever `after_restart` is going to restore. This has to happen before
+    /// Spotify is killed.
+    ///
+    /// # Arguments
+    ///
+    /// * `matcher` - Decides which processes belong to Spotify.
+    /// * `after_restart` - What will be restored.
+    pub fn take(matcher: &ProcessMatcher, after_restart: &AfterRestart) -> Self {
+        let window = if after_restart.restore_window {
+            main_window_state(&spotify_pids(matcher))
+        } else {
+            None
+        };
+        let playback = if after_restart.resume_playback {
+            SystemPlayback::new(matcher).playback_state()
+        } else {
+            PlaybackState::Unknown
+        };
+        Self { window, playback }
+    }
+}
+
+/// Waits for Spotify to respond after a restart, then restores what `snapshot` says it was doing.
+/// This blocks until Spotify responds, so it should run on its own thread.
+///
+/// # Arguments
+///
+/// * `matcher` - Decides which processes belong to Spotify.
+/// * `snapshot` - Spotify before the restart, from [`SpotifySnapshot::take`].
+/// * `after_restart` - What to restore and how long to wait.
+///
+/// # Errors
+///
+/// Returns an error if Spotify doesn't respond or its window doesn't appear before the timeout,
+/// or if it can't be told to play.
+pub fn restore_after_restart(
+    matcher: &ProcessMatcher,
+    snapshot: SpotifySnapshot,
+    after_restart: &AfterRestart,
+) -> anyhow::Result<()> {
+    let timeout = after_restart.timeout();
+    let deadline = Instant::now() + timeout;
+    let gave_up = || format!("Gave up restoring Spotify after {}s", timeout.as_secs());
+
+    while !responds(&spotify_pids(matcher)) {
+        anyhow::ensure!(
+            Instant::now() < deadline,
+            "{}: it isn't responding",
+            gave_up()
+        );
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    if after_restart.resume_playback && snapshot.playback == PlaybackState::Playing {
+        play(&spotify_pids(matcher))?;
+    }
+
+    let restore_window = after_restart
+        .restore_window
+        .then_some(snapshot.window)
+        .flatten();
+    if let Some(state @ (WindowState::Minimized | WindowState::Maximized)) = restore_window {
+        while !set_main_window_state(&spotify_pids(matcher), state)? {
+            anyhow::ensure!(
+                Instant::now() < deadline,
+                "{}: its window didn't appear",
+                gave_up()
+            );
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+    Ok(())
+}
+
+fn spotify_pids(matcher: &ProcessMatcher) -> Vec<u32> {
+    find_spotify_pids(matcher)
+        .iter()
+        .map(|pid| pid.as_u32())
+        .collect()
+}
+
+/// Finds the ID of the first window belonging to Spotify in the output of `wmctrl -lp`.
+///
+/// # Arguments
+///
+/// * `output` - What `wmctrl` printed: the window ID, desktop, PID, host and title of each window.
+/// * `pids` - The Spotify process IDs.
+pub fn find_window(output: &str, pids: &[u32]) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let id = fields.next()?;
+        let pid = fields.nth(1)?.parse::<u32>().ok()?;
+        pids.contains(&pid).then(|| id.to_owned())
+    })
+}
+
+/// Reads the output of `xprop -id <window> _NET_WM_STATE`.
+pub fn parse_wm_state(output: &str) -> WindowState {
+    if output.contains("_NET_WM_STATE_HIDDEN") {
+        WindowState::Minimized
+    } else if output.contains("_NET_WM_STATE_MAXIMIZED_VERT")
+        && output.contains("_NET_WM_STATE_MAXIMIZED_HORZ")
+    {
+        WindowState::Maximized
+    } else {
+        WindowState::Normal
+    }
+}
+
+/// Runs a command, failing if it can't be run or exits unsuccessfully.
+#[cfg(unix)]
+fn run(command: &mut Command) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run {command:?}"))?;
+    anyhow::ensure!(status.success(), "{command:?} failed: {status}");
+    Ok(())
+}
+
+/// Spotify's MPRIS interface only shows up once the main process is ready.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn responds(_pids: &[u32]) -> bool {
+    command_output(Command::new("dbus-send").args([
+        "--print-reply",
+        "--dest=org.mpris.MediaPlayer2.spotify",
+        "/org/mpris/MediaPlayer2",
+        "org.freedesktop.DBus.Properties.Get",
+        "string:org.mpris.MediaPlayer2.Player",
+        "string:PlaybackStatus",
+    ]))
+    .is_some()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn play(_pids: &[u32]) -> anyhow::Result<()> {
+    run(Command::new("dbus-send").args([
+        "--print-reply",
+        "--dest=org.mpris.MediaPlayer2.spotify",
+        "/org/mpris/MediaPlayer2",
+        "org.mpris.MediaPlayer2.Player.Play",
+    ]))
+}
+
+/// Windows are found with `wmctrl`, so this only works on X11.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn main_window(pids: &[u32]) -> Option<String> {
+    find_window(&command_output(Command::new("wmctrl").arg("-lp"))?, pids)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn main_window_state(pids: &[u32]) -> Option<WindowState> {
+    let window = main_window(pids)?;
+    command_output(Command::new("xprop").args(["-id", &window, "_NET_WM_STATE"]))
+        .map(|output| parse_wm_state(&output))
+}
+
+/// Returns `false` if Spotify has no window yet.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn set_main_window_state(pids: &[u32], state: WindowState) -> anyhow::Result<bool> {
+    let Some(window) = main_window(pids) else {
+        return Ok(false);
+    };
+    match state {
+        WindowState::Normal => {}
+        // windows are iconified with a client message to the window manager, which wmctrl can't
+        // send
+        WindowState::Minimized => {
+            run(Command::new("xdotool").args(["windowminimize", &window]))?;
+        }
+        WindowState::Maximized => run(Command::new("wmctrl").args([
+            "-i",
+            "-r",
+            &window,
+            "-b",
+            "add,maximized_vert,maximized_horz",
+        ]))?,
+    }
+    Ok(true)
+}
+
+#[cfg(target_os = "macos")]
+fn responds(_pids: &[u32]) -> bool {
+    const SCRIPT: &str =
+        r#"if application "Spotify" is running then tell application "Spotify" to player state"#;
+
+    command_output(Command::new("osascript").args(["-e", SCRIPT])).is_some_and(|s| !s.is_empty())
+}
+
+#[cfg(target_os = "macos")]
+fn play(_pids: &[u32]) -> anyhow::Result<()> {
+    run(Command::new("osascript").args(["-e", r#"tell application "Spotify" to play"#]))
+}
+
+/// Reading and changing other apps' windows needs the accessibility permission on macOS, so
+/// windows are left alone.
+#[cfg(target_os = "macos")]
+const fn main_window_state(_pids: &[u32]) -> Option<WindowState> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+#[allow(clippy::unnecessary_wraps)]
+const fn set_main_window_state(_pids: &[u32], _state: WindowState) -> anyhow::Result<bool> {
+    Ok(true)
+}
+
+/// Spotify responds once its main window handles messages.
+#[cfg(windows)]
+fn responds(pids: &[u32]) -> bool {
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_NULL};
+
+    main_window(pids).is_some_and(|hwnd| {
+        let result = unsafe {
+            SendMessageTimeoutW(
+                hwnd,
+                WM_NULL,
+                WPARAM(0),
+                LPARAM(0),
+                SMTO_ABORTIFHUNG,
+                1000,
+                None,
+            )
+        };
+        result.0 != 0
+    })
+}
+
+#[cfg(windows)]
+fn play(pids: &[u32]) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{PostMessageW, WM_APPCOMMAND};
+
+    // APPCOMMAND_MEDIA_PLAY, which plays rather than toggles like the media keys do
+    const MEDIA_PLAY: isize = 46;
+
+    let hwnd = main_window(pids).context("Spotify's window is gone")?;
+    unsafe { PostMessageW(hwnd, WM_APPCOMMAND, WPARAM(0), LPARAM(MEDIA_PLAY << 16)) }
+        .context("Failed to tell Spotify to play")
+}
+
+#[cfg(windows)]
+fn main_window_state(pids: &[u32]) -> Option<WindowState> {
+    use windows::Win32::UI::WindowsAndMessaging::{IsIconic, IsZoomed};
+
+    let hwnd = main_window(pids)?;
+    // both only read the window style, so they work while Spotify is hung
+    let state = unsafe {
+        if IsIconic(hwnd).as_bool() {
+            WindowState::Minimized
+        } else if IsZoomed(hwnd).as_bool() {
+            WindowState::Maximized
+        } else {
+            WindowState::Normal
+        }
+    };
+    Some(state)
+}
+
+/// Returns `false` if Spotify has no window yet.
+#[cfg(windows)]
+#[allow(clippy::unnecessary_wraps)]
+fn set_main_window_state(pids: &[u32], state: WindowState) -> anyhow::Result<bool> {
+    use windows::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_MAXIMIZE, SW_SHOWMINNOACTIVE};
+
+    let Some(hwnd) = main_window(pids) else {
+        return Ok(false);
+    };
+    let command = match state {
+        WindowState::Normal => return Ok(true),
+        WindowState::Minimized => SW_SHOWMINNOACTIVE,
+        WindowState::Maximized => SW_MAXIMIZE,
+    };
+    // returns whether the window was visible before, not whether it worked
+    unsafe { ShowWindow(hwnd, command) };
+    Ok(true)
+}
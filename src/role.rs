@@ -0,0 +1,117 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// What a Spotify process does. Spotify is built on Chromium, so most of its processes are
+/// Chromium children told apart by their `--type=` argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessRole {
+    /// The client itself, which starts every other process.
+    Main,
+    Renderer,
+    Gpu,
+    /// Any other Chromium child, e.g. the network and audio services, plugins or the zygote.
+    Utility,
+    /// Crashpad, which reports crashes.
+    CrashHandler,
+    /// `SpotifyMigrator`, which installs updates.
+    Updater,
+    /// `SpotifyWebHelper`, which lets web pages open Spotify.
+    WebHelper,
+}
+
+impl ProcessRole {
+    pub const ALL: [Self; 7] = [
+        Self::Main,
+        Self::Renderer,
+        Self::Gpu,
+        Self::Utility,
+        Self::CrashHandler,
+        Self::Updater,
+        Self::WebHelper,
+    ];
+
+    /// Works out the role of a Spotify process.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The process name.
+    /// * `cmd` - The command line, starting with the executable.
+    pub fn classify(name: &str, cmd: &[String]) -> Self {
+        let name = name.to_lowercase();
+        if name.contains("migrator") {
+            return Self::Updater;
+        }
+        if name.contains("webhelper") {
+            return Self::WebHelper;
+        }
+        // the macOS crash handler is an executable of its own
+        if name.contains("crashpad") {
+            return Self::CrashHandler;
+        }
+
+        match cmd.iter().find_map(|arg| arg.strip_prefix("--type=")) {
+            Some("renderer") => Self::Renderer,
+            Some("gpu-process") => Self::Gpu,
+            Some("crashpad-handler") => Self::CrashHandler,
+            Some(_) => Self::Utility,
+            // the macOS helpers are named after their type, in case the command line can't be read
+            None if name.ends_with("(renderer)") => Self::Renderer,
+            None if name.ends_with("(gpu)") => Self::Gpu,
+            None if name.starts_with("spotify helper") => Self::Utility,
+            None => Self::Main,
+        }
+    }
+}
+
+impl fmt::Display for ProcessRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Main => "main",
+            Self::Renderer => "renderer",
+            Self::Gpu => "GPU",
+            Self::Utility => "utility",
+            Self::CrashHandler => "crash handler",
+            Self::Updater => "updater",
+            Self::WebHelper => "web helper",
+        };
+        f.pad(s)
+    }
+}
+
+/// A set of roles. Small enough to be [`Copy`] like the [`crate::safety::KillPolicy`] holding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoleSet(u8);
+
+impl RoleSet {
+    pub const fn all() -> Self {
+        Self((1 << ProcessRole::ALL.len()) - 1)
+    }
+
+    pub const fn contains(self, role: ProcessRole) -> bool {
+        self.0 & (1 << role as u8) != 0
+    }
+}
+
+/// Every role.
+impl Default for RoleSet {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl FromIterator<ProcessRole> for RoleSet {
+    fn from_iter<I: IntoIterator<Item = ProcessRole>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .fold(0, |bits, role| bits | 1 << role as u8),
+        )
+    }
+}
+
+impl<'a> FromIterator<&'a ProcessRole> for RoleSet {
+    fn from_iter<I: IntoIterator<Item = &'a ProcessRole>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+
+use sysinfo::{Pid, Process, System};
+
+use crate::{
+    config::Config,
+    role::{ProcessRole, RoleSet},
+};
+
+/// Why a matching process was left alone by [`crate::process::kill_spotify_processes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkipReason {
+    /// PID 1, i.e. `init`/`launchd`, which takes the whole system down with it.
+    Init,
+    /// The spotikill process itself.
+    Spotikill,
+    /// Whatever started spotikill, e.g. the shell or the login session.
+    SpotikillParent,
+    /// The process belongs to someone else, or its owner couldn't be determined.
+    OtherUser,
+    /// The process runs as root or with an elevated Windows token.
+    Elevated,
+    /// Processes with this role aren't in `kill_roles`.
+    Role(ProcessRole),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Init => "PID 1 is never killed",
+            Self::Spotikill => "it is spotikill itself",
+            Self::SpotikillParent => "it started spotikill",
+            Self::OtherUser => "it belongs to another user",
+            Self::Elevated => "it runs with elevated privileges",
+            Self::Role(role) => return write!(f, "{role} processes aren't in kill_roles"),
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Rules for which matching processes may be killed. Critical processes are always protected,
+/// everything else is opt-in through the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KillPolicy {
+    /// Whether processes owned by other users may be killed.
+    pub kill_other_users: bool,
+    /// Whether processes running as root or elevated may be killed.
+    pub kill_elevated: bool,
+    /// Which kinds of processes may be killed.
+    pub kill_roles: RoleSet,
+}
+
+impl KillPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            kill_other_users: config.kill_other_users,
+            kill_elevated: config.kill_elevated,
+            kill_roles: config.kill_roles.iter().collect(),
+        }
+    }
+
+    /// Checks whether a process may be killed.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - The process to check. Its user and command line must have been refreshed.
+    /// * `system` - A process table holding at least this process and spotikill itself.
+    ///
+    /// # Returns
+    ///
+    /// The reason to leave the process alone, or `None` if it may be killed.
+    pub fn check(&self, proc: &Process, system: &System) -> Option<SkipReason> {
+        let own_pid = sysinfo::get_current_pid().ok();
+        let own_process = own_pid.and_then(|pid| system.process(pid));
+
+        if proc.pid() == Pid::from(1) {
+            return Some(SkipReason::Init);
+        }
+        if Some(proc.pid()) == own_pid {
+            return Some(SkipReason::Spotikill);
+        }
+        if own_process.and_then(Process::parent) == Some(proc.pid()) {
+            return Some(SkipReason::SpotikillParent);
+        }
+        let role = ProcessRole::classify(proc.name(), proc.cmd());
+        if !self.kill_roles.contains(role) {
+            return Some(SkipReason::Role(role));
+        }
+
+        // an unknown owner is treated as someone else, there's no telling who it is
+        let own_user = own_process.and_then(Process::user_id);
+        let same_user = own_user.is_some() && proc.user_id() == own_user;
+        if !self.kill_other_users && !same_user {
+            return Some(SkipReason::OtherUser);
+        }
+        if !self.kill_elevated && is_elevated(proc) {
+            return Some(SkipReason::Elevated);
+        }
+        None
+    }
+}
+
+/// Checks whether a process has an elevated token. If the token can't be read, which is the
+/// case for most elevated processes when spotikill isn't, it is assumed to be elevated.
+#[cfg(windows)]
+fn is_elevated(proc: &Process) -> bool {
+    use windows::Win32::{
+        Foundation::{CloseHandle, HANDLE},
+        Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY},
+        System::Threading::{OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION},
+    };
+
+    unsafe {
+        let Ok(process) = OpenProcess(
+            PROCESS_QUERY_LIMITED_INFORMATION,
+            false,
+            proc.pid().as_u32(),
+        ) else {
+            return true;
+        };
+        let mut token = HANDLE::default();
+        let opened = OpenProcessToken(process, TOKEN_QUERY, &mut token);
+        let _ = CloseHandle(process);
+        if opened.is_err() {
+            return true;
+        }
+
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut size = 0;
+        let queried = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(std::ptr::addr_of_mut!(elevation).cast()),
+            u32::try_from(std::mem::size_of::<TOKEN_ELEVATION>()).unwrap_or_default(),
+            &mut size,
+        );
+        let _ = CloseHandle(token);
+        queried.is_err() || elevation.TokenIsElevated != 0
+    }
+}
+
+/// Checks whether a process runs as root.
+#[cfg(not(windows))]
+fn is_elevated(proc: &Process) -> bool {
+    match proc.effective_user_id().or_else(|| proc.user_id()) {
+        Some(uid) => **uid == 0,
+        None => true,
+    }
+}
@@ -0,0 +1,368 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use anyhow::Context;
+use chrono::{
+    DateTime, Datelike, Days, Local, LocalResult, NaiveDate, NaiveDateTime, TimeDelta, TimeZone,
+    Timelike,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::playback::{PlaybackDetector, PlaybackState};
+
+/// How long to wait before checking the conditions of a postponed run again.
+const RETRY_INTERVAL: TimeDelta = TimeDelta::minutes(1);
+/// How far ahead to look for the next match of a cron expression before giving up.
+const CRON_SEARCH_LIMIT: Days = Days::new(5 * 366);
+
+/// What a schedule does when it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduledAction {
+    Kill,
+    Restart,
+}
+
+impl fmt::Display for ScheduledAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Kill => "Kill Spotify",
+            Self::Restart => "Restart Spotify",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A `[[schedule]]` table in the config file. Exactly one of `cron` and `every_hours` must be set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    pub action: ScheduledAction,
+    /// A five-field cron expression (minute, hour, day of month, month, day of week) in local
+    /// time, e.g. `"0 4 * * *"` for every day at 4 AM.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
+    /// Run every this many hours, counting from when spotikill starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_hours: Option<u64>,
+    /// Only run while Spotify is paused.
+    #[serde(default)]
+    pub only_when_paused: bool,
+    /// Only run while the user is idle. If `only_when_paused` is set too, either is enough.
+    #[serde(default)]
+    pub only_when_idle: bool,
+}
+
+impl ScheduleConfig {
+    /// Checks whether the `only_when_*` conditions currently allow this schedule to run.
+    ///
+    /// # Arguments
+    ///
+    /// * `idle_time` - How long the user has been inactive, `None` if the platform can't tell.
+    /// * `idle_threshold` - How long the user must be inactive to count as idle.
+    /// * `playback` - Tells whether Spotify is playing. If that can't be told, it doesn't count
+    ///   as paused.
+    pub fn conditions_met<P: PlaybackDetector>(
+        &self,
+        idle_time: Option<Duration>,
+        idle_threshold: Duration,
+        playback: &P,
+    ) -> bool {
+        if !self.only_when_paused && !self.only_when_idle {
+            return true;
+        }
+
+        (self.only_when_paused && playback.playback_state() == PlaybackState::Paused)
+            || (self.only_when_idle && idle_time.is_some_and(|idle| idle >= idle_threshold))
+    }
+}
+
+/// A parsed five-field cron expression. Each field is stored as a bit set of allowed values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day of month field was not `*`.
+    day_of_month_restricted: bool,
+    /// Whether the day of week field was not `*`.
+    day_of_week_restricted: bool,
+}
+
+/// Parses a single cron field such as `*`, `5`, `1-5`, `*/15` or `0,30` into a bit set.
+fn parse_cron_field(field: &str, min: u32, max: u32) -> anyhow::Result<u64> {
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .with_context(|| format!("Invalid step in {part:?}"))?;
+                anyhow::ensure!(step > 0, "Step must be positive in {part:?}");
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            let start = start
+                .parse()
+                .with_context(|| format!("Invalid range start in {part:?}"))?;
+            let end = end
+                .parse()
+                .with_context(|| format!("Invalid range end in {part:?}"))?;
+            (start, end)
+        } else {
+            let value = range
+                .parse()
+                .with_context(|| format!("Invalid value {part:?}"))?;
+            // `5/10` means "from 5 to the end, every 10"
+            (value, if step > 1 { max } else { value })
+        };
+
+        anyhow::ensure!(
+            min <= start && start <= end && end <= max,
+            "{part:?} is out of range, values must be between {min} and {max}"
+        );
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+impl FromStr for CronExpr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<_> = s.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            anyhow::bail!("Cron expression {s:?} must have exactly 5 fields");
+        };
+
+        let mut days_of_week_bits = parse_cron_field(days_of_week, 0, 7)?;
+        // both 0 and 7 mean Sunday
+        if days_of_week_bits & (1 << 7) != 0 {
+            days_of_week_bits = (days_of_week_bits | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_cron_field(minutes, 0, 59)?,
+            hours: parse_cron_field(hours, 0, 23)?,
+            days_of_month: parse_cron_field(days_of_month, 1, 31)?,
+            months: parse_cron_field(months, 1, 12)?,
+            days_of_week: days_of_week_bits,
+            day_of_month_restricted: !days_of_month.starts_with('*'),
+            day_of_week_restricted: !days_of_week.starts_with('*'),
+        })
+    }
+}
+
+impl CronExpr {
+    fn day_matches(&self, time: &NaiveDateTime) -> bool {
+        let day_of_month = self.days_of_month & (1 << time.day()) != 0;
+        let day_of_week = self.days_of_week & (1 << time.weekday().num_days_from_sunday()) != 0;
+        // like every other cron, if both day fields are restricted either one may match
+        if self.day_of_month_restricted && self.day_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+
+    /// Finds the first matching minute strictly after the given time. Local times skipped by a
+    /// DST transition never match, and those repeated by one match once.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let naive_after = after.naive_local();
+        let mut time = naive_after.with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+        let limit = naive_after.checked_add_days(CRON_SEARCH_LIMIT)?;
+
+        while time < limit {
+            if self.months & (1 << time.month()) == 0 {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(&time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + TimeDelta::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += TimeDelta::minutes(1);
+            } else {
+                // the earlier of two repeated times may already have passed, e.g. 01:30 the
+                // first time around when it's 01:10 the second time
+                let matched = match Local.from_local_datetime(&time) {
+                    LocalResult::Single(time) => Some(time).filter(|&time| time > after),
+                    LocalResult::Ambiguous(first, second) => [first, second]
+                        .into_iter()
+                        .filter(|&time| time > after)
+                        .min(),
+                    // skipped over by a DST transition
+                    LocalResult::None => None,
+                };
+                if matched.is_some() {
+                    return matched;
+                }
+                time += TimeDelta::minutes(1);
+            }
+        }
+        None
+    }
+}
+
+/// When a schedule runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recurrence {
+    Cron(CronExpr),
+    Every(TimeDelta),
+}
+
+impl TryFrom<&ScheduleConfig> for Recurrence {
+    type Error = anyhow::Error;
+
+    fn try_from(config: &ScheduleConfig) -> Result<Self, Self::Error> {
+        match (&config.cron, config.every_hours) {
+            (Some(cron), None) => Ok(Self::Cron(cron.parse()?)),
+            (None, Some(hours)) => {
+                anyhow::ensure!(hours > 0, "every_hours must be positive");
+                let hours = i64::try_from(hours).context("every_hours is too large")?;
+                TimeDelta::try_hours(hours)
+                    .map(Self::Every)
+                    .context("every_hours is too large")
+            }
+            (Some(_), Some(_)) => anyhow::bail!("A schedule can't set both cron and every_hours"),
+            (None, None) => anyhow::bail!("A schedule must set either cron or every_hours"),
+        }
+    }
+}
+
+impl Recurrence {
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            Self::Cron(cron) => cron.next_after(after),
+            Self::Every(interval) => after.checked_add_signed(*interval),
+        }
+    }
+}
+
+/// A configured schedule along with its next run.
+#[derive(Debug, Clone)]
+pub struct ScheduledJob {
+    pub config: ScheduleConfig,
+    pub recurrence: Recurrence,
+    /// When the job runs next, or `None` if it never will.
+    pub next_run: Option<DateTime<Local>>,
+    /// Whether the next run should be skipped.
+    pub skip_next: bool,
+}
+
+impl ScheduledJob {
+    /// Describes the next run, e.g. "Restart Spotify at Tue 04:00".
+    pub fn describe(&self) -> String {
+        let Some(next_run) = self.next_run else {
+            return format!("{} (never)", self.config.action);
+        };
+        let format = if next_run - Local::now() < TimeDelta::days(6) {
+            "%a %H:%M"
+        } else {
+            "%b %-d %H:%M"
+        };
+        format!("{} at {}", self.config.action, next_run.format(format))
+    }
+}
+
+/// Keeps track of when each configured schedule runs next.
+#[derive(Debug, Clone, Default)]
+pub struct Scheduler {
+    jobs: Vec<ScheduledJob>,
+}
+
+impl Scheduler {
+    /// Creates a scheduler for the given schedules.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any schedule is invalid.
+    pub fn new(configs: &[ScheduleConfig], now: DateTime<Local>) -> anyhow::Result<Self> {
+        let jobs = configs
+            .iter()
+            .enumerate()
+            .map(|(i, config)| {
+                let recurrence = Recurrence::try_from(config)
+                    .with_context(|| format!("Invalid schedule #{}", i + 1))?;
+                Ok(ScheduledJob {
+                    config: config.clone(),
+                    next_run: recurrence.next_after(now),
+                    recurrence,
+                    skip_next: false,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { jobs })
+    }
+
+    pub fn jobs(&self) -> &[ScheduledJob] {
+        &self.jobs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Gets the earliest upcoming run of any job.
+    pub fn next_wakeup(&self) -> Option<DateTime<Local>> {
+        self.jobs.iter().filter_map(|job| job.next_run).min()
+    }
+
+    /// Checks whether any job is due to run, or due to have its run skipped.
+    pub fn has_due_jobs(&self, now: DateTime<Local>) -> bool {
+        self.jobs
+            .iter()
+            .any(|job| job.next_run.is_some_and(|next_run| next_run <= now))
+    }
+
+    /// Gets the indices of every job that is due to run. Due jobs whose run was skipped are
+    /// moved on to their next run instead of being returned.
+    pub fn due_jobs(&mut self, now: DateTime<Local>) -> Vec<usize> {
+        let mut due = Vec::new();
+        for (index, job) in self.jobs.iter_mut().enumerate() {
+            let is_due = job.next_run.is_some_and(|next_run| next_run <= now);
+            if !is_due {
+                continue;
+            }
+            if job.skip_next {
+                job.skip_next = false;
+                job.next_run = job.recurrence.next_after(now);
+            } else {
+                due.push(index);
+            }
+        }
+        due
+    }
+
+    /// Marks a job as run and moves it on to its next run.
+    pub fn complete(&mut self, index: usize, now: DateTime<Local>) {
+        if let Some(job) = self.jobs.get_mut(index) {
+            job.next_run = job.recurrence.next_after(now);
+        }
+    }
+
+    /// Tries a job again shortly, for when its conditions weren't met.
+    pub fn postpone(&mut self, index: usize, now: DateTime<Local>) {
+        if let Some(job) = self.jobs.get_mut(index) {
+            job.next_run = now.checked_add_signed(RETRY_INTERVAL);
+        }
+    }
+
+    /// Toggles whether the next run of a job is skipped.
+    pub fn toggle_skip(&mut self, index: usize) {
+        if let Some(job) = self.jobs.get_mut(index) {
+            job.skip_next = !job.skip_next;
+        }
+    }
+}
@@ -0,0 +1,155 @@
+#![cfg(windows)]
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use windows::core::{ComInterface, HSTRING};
+use windows::Win32::Storage::EnhancedStorage::PKEY_AppUserModel_ID;
+use windows::Win32::System::Com::StructuredStorage::{
+    InitPropVariantFromStringAsVector, PropVariantClear,
+};
+use windows::Win32::System::Com::{CoCreateInstance, IPersistFile, CLSCTX_LOCAL_SERVER};
+use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+use windows::Win32::UI::Shell::{IShellLinkW, ShellLink};
+
+/// The user's Start Menu programs folder, relative to `%APPDATA%`.
+const START_MENU_PROGRAMS_PATH_COMPONENTS: &str = r"Microsoft\Windows\Start Menu\Programs";
+/// The user's Startup folder, relative to `%APPDATA%`. Shortcuts in here run at login.
+const STARTUP_PATH_COMPONENTS: &str = r"Microsoft\Windows\Start Menu\Programs\Startup";
+
+fn get_shortcut_path(folder: &str, shortcut_name: &str) -> anyhow::Result<PathBuf> {
+    let base_dirs = directories::BaseDirs::new().context("Failed to get home directory")?;
+    Ok(base_dirs
+        .config_dir()
+        .join(folder)
+        .join(shortcut_name)
+        .with_extension("lnk"))
+}
+
+/// Gets the path of a shortcut with the given name in the user's Start Menu.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn get_start_menu_shortcut_path(shortcut_name: &str) -> anyhow::Result<PathBuf> {
+    get_shortcut_path(START_MENU_PROGRAMS_PATH_COMPONENTS, shortcut_name)
+}
+
+/// Gets the path of a shortcut with the given name in the user's Startup folder.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn get_startup_shortcut_path(shortcut_name: &str) -> anyhow::Result<PathBuf> {
+    get_shortcut_path(STARTUP_PATH_COMPONENTS, shortcut_name)
+}
+
+/// Why a shortcut couldn't be created.
+#[derive(Debug)]
+pub enum ShortcutError {
+    /// Shortcuts must end in `.lnk`.
+    NotLnk(PathBuf),
+    /// There is already something at the shortcut path. Shortcuts can't be updated in place, so
+    /// it must be deleted first.
+    AlreadyExists(PathBuf),
+    /// The executable to link to doesn't exist.
+    ExeNotFound(PathBuf),
+    /// The Windows shell failed, e.g. because COM isn't initialized on this thread.
+    Windows(windows::core::Error),
+}
+
+impl fmt::Display for ShortcutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotLnk(path) => write!(f, "{} isn't an .lnk file", path.display()),
+            Self::AlreadyExists(path) => write!(
+                f,
+                "{} already exists, delete it and try again",
+                path.display()
+            ),
+            Self::ExeNotFound(path) => write!(f, "{} not found", path.display()),
+            Self::Windows(e) => write!(f, "Failed to create the shortcut: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ShortcutError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Windows(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<windows::core::Error> for ShortcutError {
+    fn from(e: windows::core::Error) -> Self {
+        Self::Windows(e)
+    }
+}
+
+/// Checks that [`install_shortcut`] can create a shortcut with these arguments, without touching
+/// COM.
+///
+/// # Errors
+///
+/// Returns an error if the shortcut path doesn't end in `.lnk` or already exists, or the
+/// executable doesn't exist.
+pub fn check_shortcut(exe_path: &Path, shortcut_path: &Path) -> Result<(), ShortcutError> {
+    if !shortcut_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"))
+    {
+        return Err(ShortcutError::NotLnk(shortcut_path.to_path_buf()));
+    }
+    if shortcut_path.exists() {
+        return Err(ShortcutError::AlreadyExists(shortcut_path.to_path_buf()));
+    }
+    if !exe_path.is_file() {
+        return Err(ShortcutError::ExeNotFound(exe_path.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Installs a shortcut for a given executable at the given path. COM must be initialized on the
+/// calling thread.
+///
+/// # Arguments
+///
+/// * `aumid` - The ApplicationUserModel ID of the app to link to.
+/// * `exe_path` - The path to the executable to link to.
+/// * `shortcut_path` - The path to the shortcut to create.
+///
+/// # Errors
+///
+/// Returns an error if [`check_shortcut`] fails or the Windows API does.
+pub fn install_shortcut(
+    aumid: &str,
+    exe_path: &Path,
+    shortcut_path: &Path,
+) -> Result<(), ShortcutError> {
+    check_shortcut(exe_path, shortcut_path)?;
+    unsafe {
+        let shell_link_interface: IShellLinkW =
+            CoCreateInstance(&ShellLink, None, CLSCTX_LOCAL_SERVER)?;
+        shell_link_interface.SetPath(&HSTRING::from(exe_path))?;
+        // TODO: research what the arguments are for
+        shell_link_interface.SetArguments(&HSTRING::from(""))?;
+
+        let property_store_interface: IPropertyStore = shell_link_interface.cast()?;
+        let mut propvar = InitPropVariantFromStringAsVector(&HSTRING::from(aumid))?;
+        property_store_interface.SetValue(&PKEY_AppUserModel_ID, &propvar)?;
+        property_store_interface.Commit()?;
+        // PROPVARIANT doesn't implement Drop, it must be freed manully
+        PropVariantClear(&mut propvar)?;
+
+        let saveable_shortcut: IPersistFile = shell_link_interface.cast()?;
+        // second param says to use the first param as the save path
+        saveable_shortcut.Save(&HSTRING::from(shortcut_path), true)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Days, Local, NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::config::project_dirs;
+
+const SNOOZE_FILE_NAME: &str = "snooze.toml";
+
+/// How long to snooze for, as offered in the tray menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnoozeLength {
+    Hour,
+    /// Until midnight.
+    UntilTomorrow,
+    Indefinitely,
+}
+
+/// Whether automatic actions, i.e. scheduled runs, are suspended. Anything done from the menu or
+/// the command line still works while snoozed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Snooze {
+    #[default]
+    Off,
+    Until(DateTime<Local>),
+    Indefinitely,
+}
+
+/// The snooze file. It only exists while snoozed, and without `until` the snooze never ends.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SnoozeFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    until: Option<DateTime<Local>>,
+}
+
+impl Snooze {
+    /// Starts a snooze of the given length.
+    pub fn start(length: SnoozeLength, now: DateTime<Local>) -> Self {
+        let until = match length {
+            SnoozeLength::Hour => now.checked_add_signed(TimeDelta::hours(1)),
+            SnoozeLength::UntilTomorrow => now
+                .date_naive()
+                .checked_add_days(Days::new(1))
+                .and_then(|tomorrow| {
+                    tomorrow
+                        .and_time(NaiveTime::MIN)
+                        .and_local_timezone(Local)
+                        .earliest()
+                }),
+            SnoozeLength::Indefinitely => None,
+        };
+        // a snooze that can't be represented might as well not end
+        until.map_or(Self::Indefinitely, Self::Until)
+    }
+
+    /// Checks whether automatic actions are suspended at `now`.
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        match self {
+            Self::Off => false,
+            Self::Until(until) => now < *until,
+            Self::Indefinitely => true,
+        }
+    }
+
+    /// Gets the snooze as it is at `now`, i.e. [`Snooze::Off`] once it has run out.
+    #[must_use]
+    pub fn at(self, now: DateTime<Local>) -> Self {
+        if self.is_active(now) {
+            self
+        } else {
+            Self::Off
+        }
+    }
+
+    /// Describes the snooze for the tooltip, e.g. "Snoozed until 15:30", or `None` if it's off.
+    pub fn describe(&self, now: DateTime<Local>) -> Option<String> {
+        match self.at(now) {
+            Self::Off => None,
+            Self::Until(until) => {
+                let format = if until.date_naive() == now.date_naive() {
+                    "%H:%M"
+                } else {
+                    "%a %H:%M"
+                };
+                Some(format!("Snoozed until {}", until.format(format)))
+            }
+            Self::Indefinitely => Some("Snoozed".to_owned()),
+        }
+    }
+
+    /// Gets the path to the snooze file. The file may not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined.
+    pub fn path() -> anyhow::Result<PathBuf> {
+        Ok(project_dirs()?.data_local_dir().join(SNOOZE_FILE_NAME))
+    }
+
+    /// Loads the snooze saved by [`Snooze::save`], so it survives restarts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snooze file exists but cannot be read or parsed.
+    pub fn load() -> anyhow::Result<Self> {
+        Self::read(&Self::path()?)
+    }
+
+    /// Saves the snooze, or deletes the snooze file if it's off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snooze file can't be written or deleted.
+    pub fn save(&self) -> anyhow::Result<()> {
+        self.write(&Self::path()?)
+    }
+
+    /// Reads a snooze file, or [`Snooze::Off`] if there isn't one at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::Off),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let file: SnoozeFile = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(file.until.map_or(Self::Indefinitely, Self::Until))
+    }
+
+    /// Writes a snooze file to `path`, or deletes it if the snooze is off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written or deleted.
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let file = match self {
+            Self::Off => {
+                return match fs::remove_file(path) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                        Err(e).with_context(|| format!("Failed to delete {}", path.display()))
+                    }
+                    _ => Ok(()),
+                };
+            }
+            Self::Until(until) => SnoozeFile {
+                until: Some(*until),
+            },
+            Self::Indefinitely => SnoozeFile::default(),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let contents = toml::to_string(&file).context("Failed to serialize the snooze")?;
+        fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
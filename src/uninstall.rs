@@ -0,0 +1,345 @@
+#[cfg(windows)]
+use std::{env, io, os::windows::process::CommandExt, path::Path};
+use std::{fs, path::PathBuf, process::Command};
+
+use anyhow::Context;
+#[cfg(windows)]
+use const_format::formatcp;
+#[cfg(windows)]
+use windows::core::{HSTRING, PCWSTR};
+#[cfg(windows)]
+use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+#[cfg(windows)]
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegCreateKeyExW, RegDeleteTreeW, RegGetValueW, RegSetValueExW, HKEY,
+    HKEY_CURRENT_USER, KEY_WRITE, REG_DWORD, REG_OPTION_NON_VOLATILE, REG_SZ, RRF_RT_REG_SZ,
+};
+#[cfg(windows)]
+use windows::Win32::System::Threading::CREATE_NO_WINDOW;
+
+use crate::constants::CARGO_PKG_NAME;
+use crate::manifest::{InstallManifest, RemovalReport};
+#[cfg(windows)]
+use crate::shortcut::{get_start_menu_shortcut_path, get_startup_shortcut_path};
+
+/// The per-user key "Apps & features" lists installed apps from.
+#[cfg(windows)]
+pub const UNINSTALL_KEY: &str =
+    formatcp!(r"Software\Microsoft\Windows\CurrentVersion\Uninstall\{CARGO_PKG_NAME}");
+
+/// An open registry key, closed on drop.
+#[cfg(windows)]
+struct RegKey(HKEY);
+
+#[cfg(windows)]
+impl RegKey {
+    fn create(path: &str) -> windows::core::Result<Self> {
+        let mut key = HKEY::default();
+        unsafe {
+            RegCreateKeyExW(
+                HKEY_CURRENT_USER,
+                &HSTRING::from(path),
+                0,
+                PCWSTR::null(),
+                REG_OPTION_NON_VOLATILE,
+                KEY_WRITE,
+                None,
+                &mut key,
+                None,
+            )?;
+        }
+        Ok(Self(key))
+    }
+
+    fn set_string(&self, name: &str, value: &str) -> windows::core::Result<()> {
+        // REG_SZ is a null-terminated UTF-16 string
+        let data: Vec<u8> = value
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        unsafe { RegSetValueExW(self.0, &HSTRING::from(name), 0, REG_SZ, Some(&data)) }
+    }
+
+    fn set_dword(&self, name: &str, value: u32) -> windows::core::Result<()> {
+        unsafe {
+            RegSetValueExW(
+                self.0,
+                &HSTRING::from(name),
+                0,
+                REG_DWORD,
+                Some(&value.to_le_bytes()),
+            )
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for RegKey {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = RegCloseKey(self.0);
+        }
+    }
+}
+
+/// Registers spotikill in "Apps & features" for the current user, so it can be uninstalled like
+/// any other app. An existing entry is overwritten.
+///
+/// # Arguments
+///
+/// * `exe` - The installed executable, which handles `uninstall`.
+/// * `install_dir` - The directory spotikill is installed in. It is deleted on uninstall.
+///
+/// # Errors
+///
+/// Returns an error if the registry can't be written.
+#[cfg(windows)]
+pub fn register(exe: &Path, install_dir: &Path) -> anyhow::Result<()> {
+    let key = RegKey::create(UNINSTALL_KEY)
+        .with_context(|| format!(r"Failed to create HKCU\{UNINSTALL_KEY}"))?;
+    let exe = exe.display();
+    let icon = install_dir
+        .join(crate::resources::RESOURCE_DIR_NAME)
+        .join("app-icon.ico");
+    let strings = [
+        ("DisplayName", CARGO_PKG_NAME.to_owned()),
+        (
+            "DisplayVersion",
+            crate::update::current_version().to_string(),
+        ),
+        ("Publisher", "dablenparty".to_owned()),
+        ("Comments", env!("CARGO_PKG_DESCRIPTION").to_owned()),
+        ("DisplayIcon", icon.display().to_string()),
+        ("InstallLocation", install_dir.display().to_string()),
+        (
+            "InstallDate",
+            chrono::Local::now().format("%Y%m%d").to_string(),
+        ),
+        ("UninstallString", format!("\"{exe}\" uninstall")),
+        ("QuietUninstallString", format!("\"{exe}\" uninstall --yes")),
+    ];
+    for (name, value) in strings {
+        key.set_string(name, &value)
+            .with_context(|| format!("Failed to write {name}"))?;
+    }
+    // in KB, shown next to the app in the list
+    let size_kb = u32::try_from(dir_size(install_dir) / 1024).unwrap_or(u32::MAX);
+    for (name, value) in [("NoModify", 1), ("NoRepair", 1), ("EstimatedSize", size_kb)] {
+        key.set_dword(name, value)
+            .with_context(|| format!("Failed to write {name}"))?;
+    }
+    Ok(())
+}
+
+/// Gets the total size of the files in a directory tree. Unreadable files count as empty.
+#[cfg(windows)]
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Gets the install directory recorded by [`register`], if spotikill is registered.
+#[cfg(windows)]
+pub fn install_location() -> Option<PathBuf> {
+    let key = HSTRING::from(UNINSTALL_KEY);
+    let value = HSTRING::from("InstallLocation");
+    let mut size = 0u32;
+    unsafe {
+        // the first call only gets the size in bytes, including the null terminator
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            &value,
+            RRF_RT_REG_SZ,
+            None,
+            None,
+            Some(&mut size),
+        )
+        .ok()?;
+        let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            &value,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+        .ok()?;
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        Some(PathBuf::from(String::from_utf16_lossy(&buffer[..len])))
+    }
+}
+
+/// What [`uninstall`] removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Uninstalled {
+    /// The files in the install manifest were removed, except for the ones changed since they
+    /// were installed. The install directory is deleted too if nothing else is left in it, on
+    /// Windows shortly after this process exits.
+    Recorded {
+        install_dir: PathBuf,
+        report: RemovalReport,
+    },
+    /// An install from before manifests were written. Its whole directory is deleted, on Windows
+    /// shortly after this process exits.
+    Unrecorded { install_dir: PathBuf },
+    /// Only the shortcuts or the LaunchAgent were removed, if there were any.
+    NotInstalled,
+}
+
+/// Removes spotikill: its shortcuts, its "Apps & features" entry and the files listed in its
+/// install manifest. The config is kept.
+///
+/// The install directory holds the running executable, so that is deleted by a separate process
+/// a few seconds after this one exits.
+///
+/// # Errors
+///
+/// Returns an error if anything can't be removed.
+#[cfg(windows)]
+pub fn uninstall() -> anyhow::Result<Uninstalled> {
+    // installs from before the manifest have only these to go by
+    for shortcut in [
+        get_start_menu_shortcut_path(CARGO_PKG_NAME)?,
+        get_startup_shortcut_path(CARGO_PKG_NAME)?,
+    ] {
+        match fs::remove_file(&shortcut) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(e).with_context(|| format!("Failed to delete {}", shortcut.display()));
+            }
+            _ => {}
+        }
+    }
+
+    let install_dir = install_location();
+    match unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, &HSTRING::from(UNINSTALL_KEY)) } {
+        Err(e) if e.code() != ERROR_FILE_NOT_FOUND.to_hresult() => {
+            return Err(e).with_context(|| format!(r"Failed to delete HKCU\{UNINSTALL_KEY}"));
+        }
+        _ => {}
+    }
+
+    let manifest_path = InstallManifest::path()?;
+    if let Some(manifest) = InstallManifest::read(&manifest_path)? {
+        return uninstall_recorded(manifest, &manifest_path);
+    }
+    let Some(install_dir) = install_dir else {
+        return Ok(Uninstalled::NotInstalled);
+    };
+    // never delete a directory that doesn't look like an install, whatever the registry says
+    let exe = install_dir.join(CARGO_PKG_NAME).with_extension("exe");
+    anyhow::ensure!(
+        exe.is_file(),
+        "{} doesn't look like a spotikill install, delete it yourself",
+        install_dir.display()
+    );
+    schedule(
+        &format!("rmdir /S /Q \"{}\"", install_dir.display()),
+        &install_dir,
+    )?;
+    Ok(Uninstalled::Unrecorded { install_dir })
+}
+
+/// Removes the files listed in the manifest, then the manifest itself.
+#[cfg(windows)]
+fn uninstall_recorded(
+    manifest: InstallManifest,
+    manifest_path: &Path,
+) -> anyhow::Result<Uninstalled> {
+    let running_exe = env::current_exe().context("Failed to get the running executable")?;
+    // the running executable can't be deleted yet
+    let report = manifest.remove_files(&[running_exe.clone()])?;
+    let install_dir = manifest.install_dir;
+    if running_exe.starts_with(&install_dir) {
+        // rmdir without /S only deletes the directory if it is empty, so changed files survive
+        schedule(
+            &format!(
+                "del /F /Q \"{}\" & rmdir /Q \"{}\"",
+                running_exe.display(),
+                install_dir.display()
+            ),
+            &install_dir,
+        )?;
+    }
+    fs::remove_file(manifest_path)
+        .with_context(|| format!("Failed to delete {}", manifest_path.display()))?;
+    Ok(Uninstalled::Recorded {
+        install_dir,
+        report,
+    })
+}
+
+/// Runs a cmd command in the background once this process has had time to exit.
+#[cfg(windows)]
+fn schedule(command: &str, dir: &Path) -> anyhow::Result<()> {
+    // ping is the usual way to sleep in cmd without a console
+    Command::new("cmd.exe")
+        .raw_arg(format!("/C ping -n 4 127.0.0.1 >NUL & {command}"))
+        // don't keep the directory busy
+        .current_dir(env::temp_dir())
+        .creation_flags(CREATE_NO_WINDOW.0)
+        .spawn()
+        .with_context(|| format!("Failed to schedule the removal of {}", dir.display()))?;
+    Ok(())
+}
+
+/// Removes spotikill: its LaunchAgent and the files listed in its install manifest, which make up
+/// the app bundle. The config is kept.
+///
+/// Without a manifest, the bundle spotikill is running from is deleted, if it is one.
+///
+/// # Errors
+///
+/// Returns an error if anything can't be removed.
+#[cfg(target_os = "macos")]
+pub fn uninstall() -> anyhow::Result<Uninstalled> {
+    // stops the spotikill launchd started at login, fails harmlessly if the agent isn't loaded
+    if let Some(uid) = crate::playback::command_output(Command::new("id").arg("-u")) {
+        let target = format!("gui/{uid}/{}", crate::autostart::LAUNCH_AGENT_LABEL);
+        let _ = Command::new("launchctl")
+            .args(["bootout", &target])
+            .output();
+    }
+    // installs from before the manifest have only this to go by
+    crate::autostart::set_enabled(false)?;
+
+    let manifest_path = InstallManifest::path()?;
+    if let Some(manifest) = InstallManifest::read(&manifest_path)? {
+        // unlike on Windows, the running executable can be deleted
+        let report = manifest.remove_files(&[])?;
+        fs::remove_file(&manifest_path)
+            .with_context(|| format!("Failed to delete {}", manifest_path.display()))?;
+        return Ok(Uninstalled::Recorded {
+            install_dir: manifest.install_dir,
+            report,
+        });
+    }
+
+    let exe = std::env::current_exe().context("Failed to get the running executable")?;
+    // never delete a directory that isn't spotikill's bundle
+    // spotikill.app/Contents/MacOS/spotikill -> spotikill.app
+    let bundle_name = format!("{CARGO_PKG_NAME}.app");
+    let Some(install_dir) = exe.ancestors().nth(3).filter(|dir| {
+        dir.file_name()
+            .is_some_and(|name| name == bundle_name.as_str())
+    }) else {
+        return Ok(Uninstalled::NotInstalled);
+    };
+    fs::remove_dir_all(install_dir)
+        .with_context(|| format!("Failed to delete {}", install_dir.display()))?;
+    Ok(Uninstalled::Unrecorded {
+        install_dir: install_dir.to_path_buf(),
+    })
+}
@@ -0,0 +1,129 @@
+use std::{fs, path::PathBuf, process::Command, time::Duration};
+
+use anyhow::Context;
+use semver::Version;
+use serde::{Deserialize, Deserializer, Serialize};
+use url::Url;
+
+/// How long to wait for the release feed before giving up.
+const FEED_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The JSON document a release feed serves, e.g.
+///
+/// ```json
+/// { "version": "0.4.0", "url": "https://example.com/releases/0.4.0", "notes": "Bug fixes" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    /// The latest released version.
+    pub version: Version,
+    /// Where to download or read about the release. Always an `http://` or `https://` URL, so the
+    /// feed can't open local files or other apps.
+    #[serde(default, deserialize_with = "deserialize_web_url")]
+    pub url: Option<String>,
+    /// Release notes, shown in the notification.
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// Parses the release URL, rejecting anything that isn't a web page.
+fn deserialize_web_url<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(url) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let url = Url::parse(&url).map_err(serde::de::Error::custom)?;
+    match url.scheme() {
+        "http" | "https" => Ok(Some(url.into())),
+        scheme => Err(serde::de::Error::custom(format!(
+            "the release URL must be http or https, not {scheme}"
+        ))),
+    }
+}
+
+/// Gets the version of this build, without the `-debug` suffix of
+/// [`crate::constants::CARGO_PKG_VERSION`].
+///
+/// # Panics
+///
+/// Panics if the package version isn't valid semver, which Cargo doesn't allow.
+pub fn current_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("Cargo package versions are semver")
+}
+
+/// Reads the release manifest from a feed.
+///
+/// # Arguments
+///
+/// * `feed` - An `http://` or `https://` URL, a `file://` URL, or a path to a local file.
+///
+/// # Errors
+///
+/// Returns an error if the feed can't be read or isn't a valid manifest.
+pub fn fetch_manifest(feed: &str) -> anyhow::Result<ReleaseManifest> {
+    if feed.starts_with("http://") || feed.starts_with("https://") {
+        return ureq::get(feed)
+            .timeout(FEED_TIMEOUT)
+            .call()
+            .with_context(|| format!("Failed to fetch {feed}"))?
+            .into_json()
+            .with_context(|| format!("Failed to parse the release manifest at {feed}"));
+    }
+
+    let path = file_url_to_path(feed);
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse the release manifest at {}", path.display()))
+}
+
+/// Turns a `file://` URL into a path. Anything else is assumed to be a path already.
+fn file_url_to_path(feed: &str) -> PathBuf {
+    let Some(path) = feed.strip_prefix("file://") else {
+        return PathBuf::from(feed);
+    };
+    // `file:///C:/feed.json` has an extra slash in front of the drive letter
+    if cfg!(windows) && path.starts_with('/') && path.get(2..3) == Some(":") {
+        return PathBuf::from(&path[1..]);
+    }
+    PathBuf::from(path)
+}
+
+/// Checks a release feed for a newer version than `current`.
+///
+/// # Returns
+///
+/// The manifest of the newer release, or `None` if `current` is up to date.
+///
+/// # Errors
+///
+/// Returns an error if the feed can't be read or isn't a valid manifest.
+pub fn check_for_update(feed: &str, current: &Version) -> anyhow::Result<Option<ReleaseManifest>> {
+    let manifest = fetch_manifest(feed)?;
+    Ok((manifest.version > *current).then_some(manifest))
+}
+
+/// Opens a URL in the default browser. Nothing is ever downloaded or installed by spotikill
+/// itself.
+///
+/// # Errors
+///
+/// Returns an error if the browser can't be launched.
+pub fn open_in_browser(url: &str) -> anyhow::Result<()> {
+    let opener = if cfg!(windows) {
+        "explorer.exe"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let mut child = Command::new(opener)
+        .arg(url)
+        .spawn()
+        .with_context(|| format!("Failed to open {url}"))?;
+    // reap the child whenever it exits so it doesn't linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
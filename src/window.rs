@@ -0,0 +1,47 @@
+#![cfg(windows)]
+
+use windows::Win32::Foundation::HWND;
+
+/// Finds Spotify's main window: the first visible, unowned window with a title.
+pub fn main_window(pids: &[u32]) -> Option<HWND> {
+    use windows::Win32::Foundation::{BOOL, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindow, GetWindowTextLengthW, GetWindowThreadProcessId, IsWindowVisible,
+        GW_OWNER,
+    };
+
+    struct WindowSearch<'a> {
+        pids: &'a [u32],
+        found: Option<HWND>,
+    }
+
+    unsafe extern "system" fn check_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut WindowSearch);
+        let mut pid = 0;
+        GetWindowThreadProcessId(hwnd, Some(std::ptr::addr_of_mut!(pid)));
+        if search.pids.contains(&pid)
+            && IsWindowVisible(hwnd).as_bool()
+            && GetWindow(hwnd, GW_OWNER).0 == 0
+            && GetWindowTextLengthW(hwnd) > 0
+        {
+            search.found = Some(hwnd);
+            // stop enumerating
+            return false.into();
+        }
+        true.into()
+    }
+
+    let mut search = WindowSearch { pids, found: None };
+    let lparam = LPARAM(std::ptr::addr_of_mut!(search) as isize);
+    // stopping early makes EnumWindows return an error, so only the result counts
+    let _ = unsafe { EnumWindows(Some(check_window), lparam) };
+    search.found
+}
+
+/// Checks whether Spotify's main window is hung, which Windows decides once the window hasn't
+/// handled a message for 5 seconds.
+pub fn main_window_hung(pids: &[u32]) -> bool {
+    use windows::Win32::UI::WindowsAndMessaging::IsHungAppWindow;
+
+    main_window(pids).is_some_and(|hwnd| unsafe { IsHungAppWindow(hwnd) }.as_bool())
+}
@@ -0,0 +1,213 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use semver::Version;
+use spotikill::{
+    bundle::{AppBundle, BundleConfig, InfoPlist},
+    resources::Resource,
+};
+
+/// Builds a minimal bundle in `dir` whose executable contains `version`.
+fn build_bundle(dir: &Path, version: &str) -> AppBundle {
+    let bundle = AppBundle::new(dir, "spotikill");
+    bundle.create_skeleton().unwrap();
+    fs::write(bundle.executable(), version).unwrap();
+    fs::write(bundle.info_plist(), "<plist/>").unwrap();
+    fs::write(bundle.resources_dir().join("app-icon.png"), "png").unwrap();
+    bundle
+}
+
+#[test]
+fn layout_matches_macos_bundles() {
+    let dir = tempfile::tempdir().unwrap();
+    let bundle = build_bundle(dir.path(), "1");
+
+    let root = dir.path().join("spotikill.app");
+    assert_eq!(bundle.root(), root);
+    assert_eq!(
+        bundle.executable(),
+        root.join("Contents").join("MacOS").join("spotikill")
+    );
+    assert_eq!(
+        bundle.info_plist(),
+        root.join("Contents").join("Info.plist")
+    );
+    assert!(bundle.executable().is_file());
+    assert!(bundle.resources_dir().join("app-icon.png").is_file());
+}
+
+#[test]
+fn skeleton_replaces_previous_build() {
+    let dir = tempfile::tempdir().unwrap();
+    let bundle = build_bundle(dir.path(), "1");
+    let stale = bundle.resources_dir().join("stale.txt");
+    fs::write(&stale, "").unwrap();
+
+    bundle.create_skeleton().unwrap();
+    assert!(!stale.exists());
+    assert!(bundle.resources_dir().is_dir());
+}
+
+#[test]
+fn install_replaces_existing_version() {
+    let build_dir = tempfile::tempdir().unwrap();
+    let applications = tempfile::tempdir().unwrap();
+
+    let old = build_bundle(build_dir.path(), "old");
+    fs::write(old.resources_dir().join("removed.txt"), "").unwrap();
+    let installed = old.install_into(applications.path()).unwrap();
+    assert_eq!(installed.root(), applications.path().join("spotikill.app"));
+    assert_eq!(fs::read_to_string(installed.executable()).unwrap(), "old");
+
+    let new = build_bundle(build_dir.path(), "new");
+    let installed = new.install_into(applications.path()).unwrap();
+    assert_eq!(fs::read_to_string(installed.executable()).unwrap(), "new");
+    assert!(!installed.resources_dir().join("removed.txt").exists());
+
+    // nothing but the bundle is left behind
+    let entries: Vec<_> = fs::read_dir(applications.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(entries, ["spotikill.app"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn install_keeps_executable_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let build_dir = tempfile::tempdir().unwrap();
+    let applications = tempfile::tempdir().unwrap();
+    let bundle = build_bundle(build_dir.path(), "1");
+    fs::set_permissions(bundle.executable(), fs::Permissions::from_mode(0o755)).unwrap();
+
+    let installed = bundle.install_into(applications.path()).unwrap();
+    let mode = fs::metadata(installed.executable())
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o755);
+}
+
+#[test]
+fn failed_install_keeps_existing_version() {
+    let build_dir = tempfile::tempdir().unwrap();
+    let applications = tempfile::tempdir().unwrap();
+    let installed = build_bundle(build_dir.path(), "old")
+        .install_into(applications.path())
+        .unwrap();
+
+    // a bundle that was never built can't be copied
+    let missing = AppBundle::new(&build_dir.path().join("missing"), "spotikill");
+    assert!(missing.install_into(applications.path()).is_err());
+    assert_eq!(fs::read_to_string(installed.executable()).unwrap(), "old");
+    assert_eq!(fs::read_dir(applications.path()).unwrap().count(), 1);
+}
+
+const RESOURCES: [Resource; 2] = [
+    Resource {
+        file_name: "app-icon.icns",
+        contents: b"icns",
+    },
+    Resource {
+        file_name: "app-icon.png",
+        contents: b"png",
+    },
+];
+
+/// Writes a fake executable into `dir`.
+fn build_executable(dir: &Path) -> PathBuf {
+    let executable = dir.join("spotikill-build");
+    fs::write(&executable, "binary").unwrap();
+    executable
+}
+
+fn info_plist() -> InfoPlist {
+    InfoPlist::new(
+        "spotikill",
+        &Version::new(0, 3, 0),
+        &BundleConfig::default(),
+    )
+}
+
+#[test]
+fn build_assembles_complete_bundle() {
+    let inputs = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+    let executable = build_executable(inputs.path());
+
+    let bundle = AppBundle::new(out.path(), "spotikill");
+    bundle
+        .build(&executable, &info_plist(), &RESOURCES)
+        .unwrap();
+
+    let mut files: Vec<_> = walk(bundle.root())
+        .iter()
+        .map(|path| path.strip_prefix(bundle.root()).unwrap().to_owned())
+        .collect();
+    files.sort();
+    let expected: Vec<PathBuf> = [
+        "Contents/Info.plist",
+        "Contents/MacOS/spotikill",
+        "Contents/Resources/app-icon.icns",
+        "Contents/Resources/app-icon.png",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+    assert_eq!(files, expected);
+
+    assert_eq!(fs::read_to_string(bundle.executable()).unwrap(), "binary");
+    assert_eq!(
+        fs::read(bundle.resources_dir().join("app-icon.icns")).unwrap(),
+        b"icns"
+    );
+    let plist: InfoPlist = plist::from_file(bundle.info_plist()).unwrap();
+    assert_eq!(plist, info_plist());
+    assert!(bundle.resources_dir().join(&plist.icon_file).is_file());
+}
+
+#[test]
+fn build_rejects_inconsistent_inputs_without_touching_disk() {
+    let inputs = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+    let executable = build_executable(inputs.path());
+    let bundle = AppBundle::new(out.path(), "spotikill");
+
+    let mut other_executable = info_plist();
+    other_executable.executable = "other".to_owned();
+    assert!(bundle
+        .build(&executable, &other_executable, &RESOURCES)
+        .is_err());
+
+    // without the .icns the plist names
+    assert!(bundle
+        .build(&executable, &info_plist(), &RESOURCES[1..])
+        .is_err());
+
+    let missing = inputs.path().join("missing");
+    assert!(bundle.build(&missing, &info_plist(), &RESOURCES).is_err());
+
+    let mut invalid = info_plist();
+    invalid.version = "three".to_owned();
+    assert!(bundle.build(&executable, &invalid, &RESOURCES).is_err());
+
+    assert!(!bundle.root().exists());
+}
+
+/// Lists every file under `dir`.
+fn walk(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(walk(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
@@ -0,0 +1,112 @@
+use std::{fs, path::Path};
+
+use spotikill::cache::clear_cache;
+
+/// Writes a file of `len` bytes, creating its parent directories.
+fn write(path: &Path, len: usize) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, vec![0; len]).unwrap();
+}
+
+/// Lays out a cache like Spotify's, with offline downloads in `Storage/offline`. Their index only
+/// refers to `track.file`.
+fn cache(root: &Path) {
+    write(&root.join("Browser").join("Cache").join("data_0"), 100);
+    write(&root.join("Data").join("ab").join("abcdef.file"), 200);
+    let offline = root.join("Storage").join("offline");
+    fs::create_dir_all(&offline).unwrap();
+    fs::write(offline.join("offline.bnk"), "track").unwrap();
+    write(&offline.join("track.file"), 300);
+    write(&offline.join("evicted.file"), 60);
+    write(&root.join("Storage").join("stale.file"), 40);
+    write(&root.join("mercury.db"), 50);
+}
+
+#[test]
+fn keeps_offline_downloads() {
+    let dir = tempfile::tempdir().unwrap();
+    cache(dir.path());
+
+    let report = clear_cache(&[dir.path()], false, false).unwrap();
+    assert_eq!(report.size_before, 755);
+    assert_eq!(report.deleted, 450);
+    assert_eq!(
+        report.kept_offline,
+        [dir.path().join("Storage").join("offline")]
+    );
+    assert_eq!(
+        report.summary(),
+        "Cleared 450 B of 755 B, kept offline downloads"
+    );
+
+    let offline = dir.path().join("Storage").join("offline");
+    assert!(offline.join("offline.bnk").is_file());
+    assert!(offline.join("track.file").is_file());
+    // only the files the index refers to are kept
+    assert!(!offline.join("evicted.file").exists());
+    // only the parents of the downloads are kept, not their other entries
+    assert!(!dir.path().join("Storage").join("stale.file").exists());
+    assert!(!dir.path().join("Browser").exists());
+    assert!(!dir.path().join("Data").exists());
+    assert!(!dir.path().join("mercury.db").exists());
+}
+
+#[test]
+fn keeps_offline_downloads_at_the_root() {
+    // on Windows, the cache directory itself holds the index, next to the cached files
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("offline.bnk"), [0x0a, 0x1b, 0x2c, 0x3d]).unwrap();
+    write(&root.join("0a").join("0a1b2c3d.file"), 300);
+    write(&root.join("0a").join("0a99.file"), 20);
+    write(&root.join("ff").join("ffeeddcc.file"), 200);
+    write(&root.join("mercury.db"), 50);
+
+    let report = clear_cache(&[root], false, false).unwrap();
+    assert_eq!(report.size_before, 574);
+    assert_eq!(report.deleted, 270);
+    assert_eq!(report.kept_offline, [root]);
+
+    assert!(root.join("offline.bnk").is_file());
+    assert!(root.join("0a").join("0a1b2c3d.file").is_file());
+    assert!(!root.join("0a").join("0a99.file").exists());
+    assert!(!root.join("ff").exists());
+    assert!(!root.join("mercury.db").exists());
+}
+
+#[test]
+fn includes_offline_downloads() {
+    let dir = tempfile::tempdir().unwrap();
+    cache(dir.path());
+
+    let report = clear_cache(&[dir.path()], true, false).unwrap();
+    assert_eq!(report.deleted, 755);
+    assert!(report.kept_offline.is_empty());
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn skips_missing_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    cache(dir.path());
+    let missing = dir.path().join("missing");
+
+    let report = clear_cache(&[missing.as_path(), dir.path()], true, false).unwrap();
+    assert_eq!(report.deleted, 755);
+    assert!(!missing.exists());
+}
+
+#[test]
+fn refuses_while_spotify_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    cache(dir.path());
+
+    assert!(clear_cache(&[dir.path()], true, true).is_err());
+    assert!(dir.path().join("mercury.db").is_file());
+    assert!(dir
+        .path()
+        .join("Data")
+        .join("ab")
+        .join("abcdef.file")
+        .is_file());
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use spotikill::flavour::InstallFlavour;
+
+/// `(executable, command line, cgroup, expected flavour)`
+type Case = (
+    Option<&'static str>,
+    &'static [&'static str],
+    &'static str,
+    InstallFlavour,
+);
+
+const CASES: &[Case] = &[
+    // Snap, by scope or by the path of the mounted snap
+    (
+        Some("/snap/spotify/81/usr/share/spotify/spotify"),
+        &["/snap/spotify/81/usr/share/spotify/spotify"],
+        "0::/user.slice/user-1000.slice/user@1000.service/app.slice/snap.spotify.spotify-0b9a5c5e-3f4d-4c55-9d0e-6f6d0a1b2c3d.scope\n",
+        InstallFlavour::Snap,
+    ),
+    (
+        None,
+        &["spotify", "--type=renderer"],
+        "0::/user.slice/user-1000.slice/user@1000.service/app.slice/snap.spotify.spotify-0b9a5c5e-3f4d-4c55-9d0e-6f6d0a1b2c3d.scope\n",
+        InstallFlavour::Snap,
+    ),
+    (
+        Some("/snap/spotify/81/usr/share/spotify/spotify"),
+        &[],
+        "",
+        InstallFlavour::Snap,
+    ),
+    // Flatpak, by scope, by the app ID on the command line or by the sandbox's /app
+    (
+        Some("/app/extra/share/spotify/spotify"),
+        &["/app/extra/share/spotify/spotify"],
+        "0::/user.slice/user-1000.slice/user@1000.service/app.slice/app-flatpak-com.spotify.Client-1234.scope\n",
+        InstallFlavour::Flatpak,
+    ),
+    (
+        Some("/usr/bin/bwrap"),
+        &["bwrap", "--args", "41", "com.spotify.Client", "spotify"],
+        "",
+        InstallFlavour::Flatpak,
+    ),
+    (
+        Some("/app/extra/share/spotify/spotify"),
+        &[],
+        "",
+        InstallFlavour::Flatpak,
+    ),
+    // the official Debian package
+    (
+        Some("/usr/share/spotify/spotify"),
+        &["/usr/share/spotify/spotify"],
+        "0::/user.slice/user-1000.slice/session-2.scope\n",
+        InstallFlavour::Deb,
+    ),
+    // anything else, e.g. a tarball
+    (
+        Some("/opt/spotify/spotify"),
+        &["/opt/spotify/spotify"],
+        "0::/user.slice/user-1000.slice/session-2.scope\n",
+        InstallFlavour::Native,
+    ),
+    (None, &[], "", InstallFlavour::Native),
+    // Windows has no cgroups, so both the installer and the Microsoft Store are native
+    (
+        Some(r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe"),
+        &[r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe"],
+        "",
+        InstallFlavour::Native,
+    ),
+    (
+        Some(r"C:\Program Files\WindowsApps\SpotifyAB.SpotifyMusic_1.230.1135.0_x64__zpdnekdrzrea0\Spotify.exe"),
+        &[
+            r"C:\Program Files\WindowsApps\SpotifyAB.SpotifyMusic_1.230.1135.0_x64__zpdnekdrzrea0\Spotify.exe",
+            "--type=gpu-process",
+        ],
+        "",
+        InstallFlavour::Native,
+    ),
+    // macOS
+    (
+        Some("/Applications/Spotify.app/Contents/MacOS/Spotify"),
+        &["/Applications/Spotify.app/Contents/MacOS/Spotify"],
+        "",
+        InstallFlavour::Native,
+    ),
+];
+
+#[test]
+fn detects_flavour() {
+    for &(exe, cmd, cgroup, expected) in CASES {
+        let cmd: Vec<_> = cmd.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            InstallFlavour::detect(exe.map(Path::new), &cmd, cgroup),
+            expected,
+            "{exe:?} {cmd:?} {cgroup:?}"
+        );
+    }
+}
+
+#[test]
+fn other_snaps_are_not_spotify() {
+    let cgroup = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/snap.spotify-qt.spotify-qt-1a2b.scope\n";
+    assert_eq!(
+        InstallFlavour::detect(Some(Path::new("/opt/spotify/spotify")), &[], cgroup),
+        InstallFlavour::Native
+    );
+}
@@ -0,0 +1,81 @@
+use std::{io::Cursor, path::Path};
+
+use image::{GenericImageView, RgbaImage};
+use spotikill::{
+    iconset::{encode_icns, encode_ico, generate, read_icns_entries, ICNS_ENTRIES, ICO_SIZES},
+    resources::APP_ICON_PNG,
+};
+
+const SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/resources/app-icon.png");
+
+fn source() -> RgbaImage {
+    image::open(SOURCE).unwrap().to_rgba8()
+}
+
+#[test]
+fn ico_has_every_size() {
+    let ico = encode_ico(&source()).unwrap();
+    let icon_dir = ico::IconDir::read(Cursor::new(ico)).unwrap();
+
+    let mut sizes = Vec::new();
+    for entry in icon_dir.entries() {
+        assert_eq!(entry.width(), entry.height());
+        let image = entry.decode().unwrap();
+        assert_eq!(image.width(), entry.width());
+        sizes.push(entry.width());
+    }
+    assert_eq!(sizes, ICO_SIZES);
+}
+
+#[test]
+fn icns_has_every_size() {
+    let icns = encode_icns(&source()).unwrap();
+    let entries = read_icns_entries(&icns).unwrap();
+
+    assert_eq!(entries.len(), ICNS_ENTRIES.len());
+    for ((kind, data), (expected_kind, size)) in entries.iter().zip(ICNS_ENTRIES) {
+        assert_eq!(*kind, expected_kind);
+        let image = image::load_from_memory_with_format(data, image::ImageFormat::Png).unwrap();
+        assert_eq!(
+            image.dimensions(),
+            (size, size),
+            "{}",
+            String::from_utf8_lossy(kind)
+        );
+    }
+}
+
+#[test]
+fn generated_files_match_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let icons = generate(Path::new(SOURCE), dir.path()).unwrap();
+
+    let png = image::open(&icons.png).unwrap().to_rgba8();
+    assert_eq!(png, source());
+    let ico = ico::IconDir::read(std::fs::File::open(&icons.ico).unwrap()).unwrap();
+    assert_eq!(ico.entries().len(), ICO_SIZES.len());
+    let icns = std::fs::read(&icons.icns).unwrap();
+    assert_eq!(read_icns_entries(&icns).unwrap().len(), ICNS_ENTRIES.len());
+}
+
+#[test]
+fn embedded_png_matches_source() {
+    let png = image::load_from_memory(APP_ICON_PNG).unwrap().to_rgba8();
+    assert_eq!(png, source());
+}
+
+#[test]
+fn rejects_non_square_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("wide.png");
+    RgbaImage::new(32, 16).save(&source).unwrap();
+    assert!(generate(&source, &dir.path().join("out")).is_err());
+}
+
+#[test]
+fn rejects_malformed_icns() {
+    let icns = encode_icns(&source()).unwrap();
+    assert!(read_icns_entries(&icns[..icns.len() - 1]).is_err());
+    assert!(read_icns_entries(b"icnx\0\0\0\x08").is_err());
+    assert!(read_icns_entries(b"icns\0\0\0\x10ic07\0\0\0\x02").is_err());
+}
@@ -0,0 +1,116 @@
+use std::fs;
+
+use semver::Version;
+use spotikill::bundle::{BundleConfig, InfoPlist};
+
+const GOLDEN: &str = include_str!("golden/Info.plist");
+const GOLDEN_EXTRA: &str = include_str!("golden/Info.extra.plist");
+
+fn version() -> Version {
+    Version::parse("0.3.0-debug").unwrap()
+}
+
+fn to_xml(plist: &InfoPlist) -> String {
+    String::from_utf8(plist.to_xml().unwrap()).unwrap()
+}
+
+/// Compares against a golden file, ignoring line endings git may have changed on checkout.
+fn assert_golden(actual: &str, golden: &str) {
+    assert_eq!(actual, golden.replace("\r\n", "\n"));
+}
+
+#[test]
+fn matches_golden_file() {
+    let config = BundleConfig {
+        ui_element: Some(true),
+        ..Default::default()
+    };
+    assert_golden(
+        &to_xml(&InfoPlist::new("spotikill", &version(), &config)),
+        GOLDEN,
+    );
+}
+
+#[test]
+fn extra_keys_and_odd_characters_match_golden_file() {
+    let config = BundleConfig {
+        minimum_system_version: Some("10.15".to_owned()),
+        copyright: Some("© 2024 <Hunter> & \"friends\"".to_owned()),
+        ui_element: Some(false),
+    };
+    let plist = InfoPlist::new("spot<kill> & co", &version(), &config);
+    assert_eq!(plist.identifier, "com.dablenparty.spot-kill----co");
+    assert_golden(&to_xml(&plist), GOLDEN_EXTRA);
+}
+
+#[test]
+fn round_trips_through_plist_parser() {
+    let config = BundleConfig {
+        copyright: Some("a & b".to_owned()),
+        ..Default::default()
+    };
+    let plist = InfoPlist::new("spotikill", &version(), &config);
+    let parsed: InfoPlist = plist::from_bytes(&plist.to_xml().unwrap()).unwrap();
+    assert_eq!(parsed, plist);
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    let valid = InfoPlist::new("spotikill", &version(), &BundleConfig::default());
+    valid.validate().unwrap();
+
+    let mut cases = Vec::new();
+    let mut plist = valid.clone();
+    plist.identifier = "com.example.spoti kill".to_owned();
+    cases.push(plist);
+    let mut plist = valid.clone();
+    plist.executable = "bin/spotikill".to_owned();
+    cases.push(plist);
+    let mut plist = valid.clone();
+    plist.name = String::new();
+    cases.push(plist);
+    let mut plist = valid.clone();
+    plist.version = "0.3.0-beta".to_owned();
+    cases.push(plist);
+    let mut plist = valid.clone();
+    plist.short_version = "1.2.3.4".to_owned();
+    cases.push(plist);
+    let mut plist = valid;
+    plist.minimum_system_version = Some("Catalina".to_owned());
+    cases.push(plist);
+
+    for plist in cases {
+        assert!(plist.to_xml().is_err(), "accepted {plist:?}");
+    }
+}
+
+#[test]
+fn reads_config_from_cargo_manifest() {
+    let manifest = r#"
+        [package]
+        name = "spotikill"
+
+        [package.metadata.bundle]
+        minimum_system_version = "10.15"
+        ui_element = false
+    "#;
+    let config = BundleConfig::from_manifest(manifest).unwrap();
+    assert_eq!(config.minimum_system_version.as_deref(), Some("10.15"));
+    assert_eq!(config.copyright, None);
+    assert_eq!(config.ui_element, Some(false));
+
+    let bare = "[package]\nname = \"spotikill\"\n";
+    assert_eq!(
+        BundleConfig::from_manifest(bare).unwrap(),
+        BundleConfig::default()
+    );
+    let typo = "[package]\n[package.metadata.bundle]\ncopyrite = \"x\"\n";
+    assert!(BundleConfig::from_manifest(typo).is_err());
+}
+
+#[test]
+fn own_manifest_is_valid() {
+    let manifest = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).unwrap();
+    let config = BundleConfig::from_manifest(&manifest).unwrap();
+    InfoPlist::for_spotikill(&config).validate().unwrap();
+}
@@ -0,0 +1,102 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use spotikill::manifest::{sha256_file, FileStatus, InstallManifest};
+
+/// Writes a fake install into `dir` and records it.
+fn install(dir: &Path) -> (InstallManifest, Vec<PathBuf>) {
+    let resources = dir.join("resources");
+    fs::create_dir_all(&resources).unwrap();
+    let files = vec![
+        dir.join("spotikill.exe"),
+        dir.join("config.default.toml"),
+        resources.join("app-icon.png"),
+    ];
+    for file in &files {
+        fs::write(file, file.to_string_lossy().as_bytes()).unwrap();
+    }
+    (InstallManifest::new(dir, &files).unwrap(), files)
+}
+
+#[test]
+fn hashes_are_sha256_hex() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("abc");
+    fs::write(&path, "abc").unwrap();
+    assert_eq!(
+        sha256_file(&path).unwrap(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn round_trips_through_toml() {
+    let dir = tempfile::tempdir().unwrap();
+    let (manifest, _) = install(dir.path());
+    let path = dir.path().join("data").join("install-manifest.toml");
+
+    assert_eq!(InstallManifest::read(&path).unwrap(), None);
+    manifest.write(&path).unwrap();
+    assert_eq!(InstallManifest::read(&path).unwrap(), Some(manifest));
+}
+
+#[test]
+fn detects_modified_and_missing_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let (manifest, files) = install(dir.path());
+    assert!(manifest.modified_files().unwrap().is_empty());
+
+    fs::write(&files[1], "edited").unwrap();
+    fs::remove_file(&files[2]).unwrap();
+    let statuses: Vec<_> = manifest
+        .files
+        .iter()
+        .map(|file| file.status().unwrap())
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            FileStatus::Unchanged,
+            FileStatus::Modified,
+            FileStatus::Missing
+        ]
+    );
+    assert_eq!(manifest.modified_files().unwrap()[0].path, files[1]);
+}
+
+#[test]
+fn removal_keeps_modified_and_kept_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let install_dir = dir.path().join("spotikill");
+    let (manifest, files) = install(&install_dir);
+    fs::write(&files[1], "edited").unwrap();
+
+    let report = manifest.remove_files(&files[..1]).unwrap();
+    assert_eq!(report.removed, [files[2].clone()]);
+    assert_eq!(report.kept_modified, [files[1].clone()]);
+    assert!(report.missing.is_empty());
+    assert!(files[0].is_file());
+    assert!(files[1].is_file());
+    // the emptied resources directory goes, the install directory still has files in it
+    assert!(!install_dir.join("resources").exists());
+    assert!(install_dir.is_dir());
+}
+
+#[test]
+fn removal_deletes_emptied_install_dir_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let install_dir = dir.path().join("spotikill");
+    let (manifest, files) = install(&install_dir);
+
+    let report = manifest.remove_files(&[]).unwrap();
+    assert_eq!(report.removed, files);
+    assert!(!install_dir.exists());
+    assert!(dir.path().is_dir());
+
+    // removing again finds nothing to do
+    let report = manifest.remove_files(&[]).unwrap();
+    assert!(report.removed.is_empty());
+    assert_eq!(report.missing, files);
+}
@@ -0,0 +1,76 @@
+use spotikill::{
+    flavour::InstallFlavour,
+    process::{KillReport, ProcessInfo},
+    role::ProcessRole,
+};
+use sysinfo::Pid;
+
+fn process(pid: usize, parent: Option<usize>, role: ProcessRole) -> ProcessInfo {
+    ProcessInfo {
+        pid: Pid::from(pid),
+        parent: parent.map(Pid::from),
+        name: "spotify".to_owned(),
+        exe: None,
+        flavour: InstallFlavour::default(),
+        role,
+        memory: 512 * 1024 * 1024,
+        cpu_usage: 2.5,
+    }
+}
+
+#[test]
+fn summarizes_verified_kill() {
+    let report = KillReport {
+        killed: vec![
+            process(10, Some(1), ProcessRole::Main),
+            process(11, Some(10), ProcessRole::Renderer),
+        ],
+        ..KillReport::default()
+    };
+    assert_eq!(
+        report.summary(),
+        "Freed 1.0 GB RAM and 5.0% CPU across 2 processes"
+    );
+    assert_eq!(
+        report.main_process().map(|proc| proc.pid),
+        Some(Pid::from(10))
+    );
+}
+
+#[test]
+fn reports_processes_still_running() {
+    let report = KillReport {
+        killed: vec![
+            process(10, Some(1), ProcessRole::Main),
+            process(11, Some(10), ProcessRole::Renderer),
+        ],
+        lingering: vec![process(11, Some(10), ProcessRole::Renderer)],
+        respawned: vec![process(20, Some(1), ProcessRole::Main)],
+        ..KillReport::default()
+    };
+    assert_eq!(
+        report.summary(),
+        "Freed 1.0 GB RAM and 5.0% CPU across 2 processes, 1 still exiting, respawned processes \
+         detected"
+    );
+    let text = report.to_string();
+    assert!(text.contains("  still exiting spotify (11)\n"));
+    assert!(text.contains("  respawned spotify (20)\n"));
+}
+
+#[test]
+fn reports_failed_kills() {
+    let report = KillReport {
+        killed: vec![process(11, Some(10), ProcessRole::Renderer)],
+        failed: vec![process(10, Some(1), ProcessRole::Main)],
+        ..KillReport::default()
+    };
+    // only what was actually killed counts as freed
+    assert_eq!(
+        report.summary(),
+        "Freed 512.0 MB RAM and 2.5% CPU across 1 process, failed to kill 1"
+    );
+    assert!(report
+        .to_string()
+        .contains("  failed to kill spotify (10)\n"));
+}
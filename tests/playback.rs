@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use spotikill::{
+    confirm::ConfirmPolicy,
+    playback::{audio_assertion_state, sink_input_state, PlaybackDetector, PlaybackState},
+    schedule::{ScheduleConfig, ScheduledAction},
+};
+
+/// Always says the same thing.
+struct FakePlayback(PlaybackState);
+
+impl PlaybackDetector for FakePlayback {
+    fn playback_state(&self) -> PlaybackState {
+        self.0
+    }
+}
+
+const ALL_STATES: [PlaybackState; 3] = [
+    PlaybackState::Playing,
+    PlaybackState::Paused,
+    PlaybackState::Unknown,
+];
+
+const SINK_INPUTS: &str = r#"Sink Input #41
+	Driver: PipeWire
+	Owner Module: n/a
+	Client: 40
+	Sink: 52
+	Corked: no
+	Mute: no
+	Properties:
+		application.name = "Firefox"
+		application.process.id = "1200"
+
+Sink Input #87
+	Driver: PipeWire
+	Owner Module: n/a
+	Client: 86
+	Sink: 52
+	Corked: yes
+	Mute: no
+	Properties:
+		application.name = "spotify"
+		application.process.id = "4321"
+"#;
+
+const ASSERTIONS: &str = r#"Assertion status system-wide:
+   PreventUserIdleSystemSleep     1
+Listed by owning process:
+   pid 312(coreaudiod): [0x0000a1b200019c3d] 00:02:14 PreventUserIdleSleep named: "com.apple.audio.BuiltInSpeakerDevice.context.preventuseridlesleep"
+	Created for PID: 4321.
+   pid 98(powerd): [0x0000a1b200019c3e] 00:10:00 PreventUserIdleSystemSleep named: "Powerd - Prevent sleep while display is on"
+	Created for PID: 5555.
+"#;
+
+fn schedule(only_when_paused: bool) -> ScheduleConfig {
+    ScheduleConfig {
+        action: ScheduledAction::Restart,
+        cron: Some("0 4 * * *".to_owned()),
+        every_hours: None,
+        only_when_paused,
+        only_when_idle: false,
+    }
+}
+
+#[test]
+fn confirm_policy() {
+    for state in ALL_STATES {
+        let playback = FakePlayback(state);
+        assert!(ConfirmPolicy::Always.must_ask(&playback));
+        assert!(!ConfirmPolicy::Never.must_ask(&playback));
+        assert_eq!(
+            ConfirmPolicy::WhenPlaying.must_ask(&playback),
+            state == PlaybackState::Playing
+        );
+    }
+}
+
+#[test]
+fn only_when_paused() {
+    let idle_threshold = Duration::from_secs(600);
+    for state in ALL_STATES {
+        let playback = FakePlayback(state);
+        assert!(schedule(false).conditions_met(None, idle_threshold, &playback));
+        // not knowing doesn't count as paused
+        assert_eq!(
+            schedule(true).conditions_met(None, idle_threshold, &playback),
+            state == PlaybackState::Paused
+        );
+    }
+}
+
+#[test]
+fn falls_back_when_unknown() {
+    let fallback = || PlaybackState::Paused;
+    assert_eq!(
+        PlaybackState::Playing.or_else(fallback),
+        PlaybackState::Playing
+    );
+    assert_eq!(
+        PlaybackState::Unknown.or_else(fallback),
+        PlaybackState::Paused
+    );
+}
+
+#[test]
+fn sink_inputs() {
+    assert_eq!(
+        sink_input_state(SINK_INPUTS, &[4321]),
+        PlaybackState::Paused
+    );
+    assert_eq!(
+        sink_input_state(SINK_INPUTS, &[4320, 1200]),
+        PlaybackState::Playing
+    );
+    assert_eq!(sink_input_state(SINK_INPUTS, &[7]), PlaybackState::Unknown);
+    assert_eq!(sink_input_state("", &[4321]), PlaybackState::Unknown);
+}
+
+#[test]
+fn audio_assertions() {
+    assert_eq!(
+        audio_assertion_state(ASSERTIONS, &[4321]),
+        PlaybackState::Playing
+    );
+    // keeping the display on isn't audio
+    assert_eq!(
+        audio_assertion_state(ASSERTIONS, &[5555]),
+        PlaybackState::Unknown
+    );
+}
@@ -0,0 +1,326 @@
+use std::path::Path;
+
+use spotikill::{
+    matcher::{Platform, ProcessMatcher},
+    role::ProcessRole,
+};
+
+/// `(platform, process name, executable)`
+type Case = (Platform, &'static str, Option<&'static str>);
+
+const MUST_MATCH: &[Case] = &[
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        Some(r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe"),
+    ),
+    (
+        Platform::Windows,
+        "SpotifyWebHelper.exe",
+        Some(r"C:\Users\alice\AppData\Roaming\Spotify\SpotifyWebHelper.exe"),
+    ),
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        Some(r"C:\Program Files\WindowsApps\SpotifyAB.SpotifyMusic_1.230.1135.0_x64__zpdnekdrzrea0\Spotify.exe"),
+    ),
+    (
+        Platform::MacOs,
+        "Spotify",
+        Some("/Applications/Spotify.app/Contents/MacOS/Spotify"),
+    ),
+    (
+        Platform::MacOs,
+        "Spotify Helper (Renderer)",
+        Some("/Applications/Spotify.app/Contents/Frameworks/Spotify Helper (Renderer).app/Contents/MacOS/Spotify Helper (Renderer)"),
+    ),
+    (
+        Platform::MacOs,
+        "Spotify Helper (GPU)",
+        Some("/Users/alice/Applications/Spotify.app/Contents/Frameworks/Spotify Helper (GPU).app/Contents/MacOS/Spotify Helper (GPU)"),
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        Some("/usr/share/spotify/spotify"),
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        Some("/snap/spotify/81/usr/share/spotify/spotify"),
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        Some("/app/extra/share/spotify/spotify"),
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        Some("/opt/spotify/spotify"),
+    ),
+];
+
+const MUST_NOT_MATCH: &[Case] = &[
+    // third-party clients
+    (Platform::Linux, "spotifyd", Some("/usr/bin/spotifyd")),
+    (
+        Platform::Linux,
+        "spotify-tui",
+        Some("/home/alice/.cargo/bin/spotify-tui"),
+    ),
+    (Platform::Linux, "spt", Some("/home/alice/.cargo/bin/spt")),
+    (Platform::Linux, "spotify-qt", Some("/usr/bin/spotify-qt")),
+    (Platform::Linux, "ncspot", Some("/usr/bin/ncspot")),
+    (
+        Platform::Windows,
+        "spotifyd.exe",
+        Some(r"C:\Users\alice\.cargo\bin\spotifyd.exe"),
+    ),
+    (
+        Platform::MacOs,
+        "spotify_player",
+        Some("/opt/homebrew/bin/spotify_player"),
+    ),
+    // a script called "spotify" shows up as its interpreter
+    (Platform::Linux, "spotify", Some("/usr/bin/bash")),
+    // right name, but not from a Spotify install
+    (Platform::Linux, "spotify", Some("/usr/bin/spotify")),
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        Some(r"C:\Users\alice\Downloads\Spotify.exe"),
+    ),
+    (Platform::MacOs, "Spotify", Some("/tmp/Spotify")),
+    // the executable couldn't be read
+    (Platform::Linux, "spotify", None),
+    (Platform::Windows, "Spotify.exe", None),
+    // name and executable disagree
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        Some(r"C:\Users\alice\AppData\Roaming\Spotify\notepad.exe"),
+    ),
+    // names from the wrong platform
+    (
+        Platform::Linux,
+        "Spotify.exe",
+        Some("/home/alice/.wine/drive_c/Spotify/Spotify.exe"),
+    ),
+    (
+        Platform::MacOs,
+        "spotify",
+        Some("/Applications/Spotify.app/Contents/MacOS/spotify"),
+    ),
+];
+
+/// `(platform, process name, executable, command line, role)`
+type RoleCase = (
+    Platform,
+    &'static str,
+    &'static str,
+    &'static [&'static str],
+    ProcessRole,
+);
+
+/// A process of every role on every platform. Linux has no updater or web helper, it is updated
+/// by its package manager.
+const ROLES: &[RoleCase] = &[
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe",
+        &[r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe"],
+        ProcessRole::Main,
+    ),
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe",
+        &["Spotify.exe", "--type=renderer"],
+        ProcessRole::Renderer,
+    ),
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe",
+        &["Spotify.exe", "--type=gpu-process"],
+        ProcessRole::Gpu,
+    ),
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe",
+        &["Spotify.exe", "--type=utility"],
+        ProcessRole::Utility,
+    ),
+    (
+        Platform::Windows,
+        "Spotify.exe",
+        r"C:\Users\alice\AppData\Roaming\Spotify\Spotify.exe",
+        &["Spotify.exe", "--type=crashpad-handler"],
+        ProcessRole::CrashHandler,
+    ),
+    (
+        Platform::Windows,
+        "SpotifyMigrator.exe",
+        r"C:\Users\alice\AppData\Roaming\Spotify\SpotifyMigrator.exe",
+        &[],
+        ProcessRole::Updater,
+    ),
+    (
+        Platform::Windows,
+        "SpotifyWebHelper.exe",
+        r"C:\Users\alice\AppData\Roaming\Spotify\SpotifyWebHelper.exe",
+        &[],
+        ProcessRole::WebHelper,
+    ),
+    (
+        Platform::MacOs,
+        "Spotify",
+        "/Applications/Spotify.app/Contents/MacOS/Spotify",
+        &[],
+        ProcessRole::Main,
+    ),
+    (
+        Platform::MacOs,
+        "Spotify Helper (Renderer)",
+        "/Applications/Spotify.app/Contents/Frameworks/Spotify Helper (Renderer).app/Contents/MacOS/Spotify Helper (Renderer)",
+        &[],
+        ProcessRole::Renderer,
+    ),
+    (
+        Platform::MacOs,
+        "Spotify Helper (GPU)",
+        "/Applications/Spotify.app/Contents/Frameworks/Spotify Helper (GPU).app/Contents/MacOS/Spotify Helper (GPU)",
+        &[],
+        ProcessRole::Gpu,
+    ),
+    (
+        Platform::MacOs,
+        "Spotify Helper",
+        "/Applications/Spotify.app/Contents/Frameworks/Spotify Helper.app/Contents/MacOS/Spotify Helper",
+        &[],
+        ProcessRole::Utility,
+    ),
+    (
+        Platform::MacOs,
+        "crashpad_handler",
+        "/Applications/Spotify.app/Contents/Frameworks/Chromium Embedded Framework.framework/Versions/A/Helpers/crashpad_handler",
+        &[],
+        ProcessRole::CrashHandler,
+    ),
+    (
+        Platform::MacOs,
+        "SpotifyMigrator",
+        "/Applications/Spotify.app/Contents/MacOS/SpotifyMigrator",
+        &[],
+        ProcessRole::Updater,
+    ),
+    (
+        Platform::MacOs,
+        "SpotifyWebHelper",
+        "/Users/alice/Library/Application Support/Spotify/SpotifyWebHelper",
+        &[],
+        ProcessRole::WebHelper,
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        "/usr/share/spotify/spotify",
+        &["/usr/share/spotify/spotify"],
+        ProcessRole::Main,
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        "/usr/share/spotify/spotify",
+        &["/usr/share/spotify/spotify", "--type=renderer"],
+        ProcessRole::Renderer,
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        "/usr/share/spotify/spotify",
+        &["/usr/share/spotify/spotify", "--type=gpu-process"],
+        ProcessRole::Gpu,
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        "/usr/share/spotify/spotify",
+        &["/usr/share/spotify/spotify", "--type=zygote"],
+        ProcessRole::Utility,
+    ),
+    (
+        Platform::Linux,
+        "spotify",
+        "/usr/share/spotify/spotify",
+        &["/usr/share/spotify/spotify", "--type=crashpad-handler"],
+        ProcessRole::CrashHandler,
+    ),
+];
+
+fn matches(matcher: &ProcessMatcher, (_, name, exe): &Case) -> bool {
+    matcher.matches(name, exe.map(Path::new))
+}
+
+#[test]
+fn official_processes_match() {
+    for case in MUST_MATCH {
+        let matcher = ProcessMatcher::new(case.0, Vec::new());
+        assert!(matches(&matcher, case), "expected a match: {case:?}");
+    }
+}
+
+#[test]
+fn other_processes_do_not_match() {
+    for case in MUST_NOT_MATCH {
+        let matcher = ProcessMatcher::new(case.0, Vec::new());
+        assert!(!matches(&matcher, case), "expected no match: {case:?}");
+    }
+}
+
+#[test]
+fn only_windows_ignores_case() {
+    let windows = ProcessMatcher::new(Platform::Windows, Vec::new());
+    assert!(windows.matches(
+        "spotify.EXE",
+        Some(Path::new(
+            r"C:\Users\alice\AppData\Roaming\Spotify\SPOTIFY.exe"
+        ))
+    ));
+
+    let linux = ProcessMatcher::new(Platform::Linux, Vec::new());
+    assert!(!linux.matches("Spotify", Some(Path::new("/usr/share/spotify/Spotify"))));
+}
+
+#[test]
+fn excluded_processes_do_not_match() {
+    let exe = "/Applications/Spotify.app/Contents/Frameworks/Spotify Helper (GPU).app/Contents/MacOS/Spotify Helper (GPU)";
+
+    let by_name = ProcessMatcher::new(Platform::MacOs, vec!["Spotify Helper (GPU)".to_string()]);
+    assert!(!by_name.matches("Spotify Helper (GPU)", Some(Path::new(exe))));
+    assert!(by_name.matches(
+        "Spotify",
+        Some(Path::new(
+            "/Applications/Spotify.app/Contents/MacOS/Spotify"
+        ))
+    ));
+
+    let by_path = ProcessMatcher::new(Platform::MacOs, vec![exe.to_string()]);
+    assert!(!by_path.matches("Spotify Helper (GPU)", Some(Path::new(exe))));
+}
+
+#[test]
+fn every_role_matches() {
+    for &(platform, name, exe, cmd, role) in ROLES {
+        let matcher = ProcessMatcher::new(platform, Vec::new());
+        assert!(
+            matcher.matches(name, Some(Path::new(exe))),
+            "expected a match: {name} {exe}"
+        );
+        let cmd: Vec<_> = cmd.iter().map(ToString::to_string).collect();
+        assert_eq!(ProcessRole::classify(name, &cmd), role, "{name} {cmd:?}");
+    }
+}
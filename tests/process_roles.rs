@@ -0,0 +1,152 @@
+use spotikill::{
+    config::Config,
+    flavour::InstallFlavour,
+    matcher::ProcessMatcher,
+    process::{restart_spotify, KillPreview, KillReport, ProcessInfo},
+    role::{ProcessRole, RoleSet},
+    safety::{KillPolicy, SkipReason},
+};
+use sysinfo::Pid;
+
+/// `(process name, command line, role)`
+const CASES: &[(&str, &[&str], ProcessRole)] = &[
+    ("Spotify.exe", &["Spotify.exe"], ProcessRole::Main),
+    (
+        "Spotify.exe",
+        &["Spotify.exe", "--type=renderer", "--lang=en-US"],
+        ProcessRole::Renderer,
+    ),
+    (
+        "Spotify.exe",
+        &["Spotify.exe", "--type=gpu-process"],
+        ProcessRole::Gpu,
+    ),
+    (
+        "Spotify.exe",
+        &[
+            "Spotify.exe",
+            "--type=utility",
+            "--utility-sub-type=network.mojom.NetworkService",
+        ],
+        ProcessRole::Utility,
+    ),
+    (
+        "Spotify.exe",
+        &["Spotify.exe", "--type=crashpad-handler", "--database=..."],
+        ProcessRole::CrashHandler,
+    ),
+    (
+        "SpotifyMigrator.exe",
+        &["SpotifyMigrator.exe"],
+        ProcessRole::Updater,
+    ),
+    (
+        "SpotifyWebHelper.exe",
+        &["SpotifyWebHelper.exe"],
+        ProcessRole::WebHelper,
+    ),
+    (
+        "Spotify",
+        &["/Applications/Spotify.app/Contents/MacOS/Spotify"],
+        ProcessRole::Main,
+    ),
+    // the command line of another user's process can't be read on macOS
+    ("Spotify Helper (GPU)", &[], ProcessRole::Gpu),
+    ("Spotify Helper (Renderer)", &[], ProcessRole::Renderer),
+    ("Spotify Helper (Plugin)", &[], ProcessRole::Utility),
+    (
+        "spotify",
+        &["/usr/share/spotify/spotify", "--type=zygote"],
+        ProcessRole::Utility,
+    ),
+    (
+        "spotify",
+        &["/usr/share/spotify/spotify"],
+        ProcessRole::Main,
+    ),
+];
+
+fn process(pid: usize, parent: usize, role: ProcessRole) -> ProcessInfo {
+    ProcessInfo {
+        pid: Pid::from(pid),
+        parent: Some(Pid::from(parent)),
+        name: "Spotify.exe".to_owned(),
+        exe: None,
+        flavour: InstallFlavour::default(),
+        role,
+        memory: 100 * 1024 * 1024,
+        cpu_usage: 0.0,
+    }
+}
+
+#[test]
+fn classifies_processes() {
+    for &(name, cmd, role) in CASES {
+        let cmd: Vec<_> = cmd.iter().map(ToString::to_string).collect();
+        assert_eq!(ProcessRole::classify(name, &cmd), role, "{name} {cmd:?}");
+    }
+}
+
+#[test]
+fn role_sets() {
+    let all = RoleSet::default();
+    assert!(ProcessRole::ALL.iter().all(|&role| all.contains(role)));
+
+    let config: Config =
+        toml::from_str(r#"kill_roles = ["main", "renderer", "crash_handler"]"#).unwrap();
+    let policy = KillPolicy::from_config(&config);
+    assert!(policy.kill_roles.contains(ProcessRole::CrashHandler));
+    assert!(!policy.kill_roles.contains(ProcessRole::Updater));
+    assert_eq!(
+        SkipReason::Role(ProcessRole::Updater).to_string(),
+        "updater processes aren't in kill_roles"
+    );
+
+    // every role by default
+    assert_eq!(KillPolicy::from_config(&Config::default()).kill_roles, all);
+}
+
+#[test]
+fn preview_shows_roles() {
+    let preview = KillPreview {
+        targets: vec![
+            process(10, 1, ProcessRole::Main),
+            process(11, 10, ProcessRole::Renderer),
+            process(12, 10, ProcessRole::Renderer),
+            process(13, 10, ProcessRole::Gpu),
+        ],
+        skipped: Vec::new(),
+    };
+    assert_eq!(
+        preview.question(),
+        "Kill 4 Spotify processes (1 main, 2 renderer, 1 GPU) using 400.0 MB?"
+    );
+    assert!(preview
+        .to_string()
+        .contains("\n  renderer      Spotify.exe (11): 100.0 MB RAM\n"));
+}
+
+#[test]
+fn main_process_by_role() {
+    // the renderer's parent wasn't killed, but it still isn't the main process
+    let report = KillReport {
+        killed: vec![
+            process(11, 5, ProcessRole::Renderer),
+            process(10, 1, ProcessRole::Main),
+        ],
+        ..KillReport::default()
+    };
+    assert_eq!(
+        report.main_process().map(|proc| proc.pid),
+        Some(Pid::from(10))
+    );
+}
+
+#[test]
+fn restart_needs_main_role() {
+    let config: Config = toml::from_str(r#"kill_roles = ["renderer", "gpu"]"#).unwrap();
+    // refused before anything is killed
+    let err =
+        restart_spotify(&ProcessMatcher::default(), KillPolicy::from_config(&config)).unwrap_err();
+    assert!(err.to_string().contains("kill_roles"));
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use spotikill::{
+    config::Config,
+    restore::{find_window, parse_wm_state, AfterRestart, WindowState},
+};
+
+const WMCTRL: &str = "\
+0x02a00003  0 1187   laptop Terminal
+0x04600006  0 4321   laptop Spotify Premium
+0x04600009  0 4321   laptop Spotify
+";
+
+#[test]
+fn finds_spotify_window() {
+    assert_eq!(
+        find_window(WMCTRL, &[4320, 4321]).as_deref(),
+        Some("0x04600006")
+    );
+    assert_eq!(find_window(WMCTRL, &[99]), None);
+    assert_eq!(find_window("", &[4321]), None);
+}
+
+#[test]
+fn reads_window_state() {
+    assert_eq!(
+        parse_wm_state("_NET_WM_STATE(ATOM) = _NET_WM_STATE_HIDDEN"),
+        WindowState::Minimized
+    );
+    assert_eq!(
+        parse_wm_state(
+            "_NET_WM_STATE(ATOM) = _NET_WM_STATE_MAXIMIZED_VERT, _NET_WM_STATE_MAXIMIZED_HORZ"
+        ),
+        WindowState::Maximized
+    );
+    // only maximized one way
+    assert_eq!(
+        parse_wm_state("_NET_WM_STATE(ATOM) = _NET_WM_STATE_MAXIMIZED_VERT"),
+        WindowState::Normal
+    );
+    assert_eq!(
+        parse_wm_state("_NET_WM_STATE:  not found."),
+        WindowState::Normal
+    );
+}
+
+#[test]
+fn after_restart_config() {
+    let config: Config = toml::from_str(
+        "
+        [after_restart]
+        resume_playback = true
+        timeout_secs = 0
+        ",
+    )
+    .unwrap();
+    let after_restart = &config.after_restart;
+    assert!(after_restart.is_enabled());
+    assert!(!after_restart.restore_window);
+    assert_eq!(after_restart.timeout(), Duration::from_secs(1));
+    assert!(!AfterRestart::default().is_enabled());
+
+    let written = toml::to_string_pretty(&config).unwrap();
+    assert_eq!(toml::from_str::<Config>(&written).unwrap(), config);
+
+    let config: Config = toml::from_str(
+        "
+        [after_restart]
+        timeout_secs = 9223372036854775807
+        ",
+    )
+    .unwrap();
+    assert_eq!(config.after_restart.timeout(), Duration::from_secs(60 * 60));
+}
@@ -0,0 +1,390 @@
+// the tests pick their time zone through `TZ`, which chrono only reads on Unix
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, Local};
+use spotikill::{
+    playback::{PlaybackDetector, PlaybackState},
+    schedule::{CronExpr, ScheduleConfig, ScheduledAction, Scheduler},
+};
+
+/// `(cron expression, after, expected next run)`, as RFC 3339 times in New York
+type Case = (&'static str, &'static str, Option<&'static str>);
+
+const NEXT_RUNS: &[Case] = &[
+    // 2024-03-12 is a Tuesday
+    (
+        "0 4 * * *",
+        "2024-03-12T14:30:00-04:00",
+        Some("2024-03-13T04:00:00-04:00"),
+    ),
+    // strictly after
+    (
+        "30 14 * * *",
+        "2024-03-12T14:30:00-04:00",
+        Some("2024-03-13T14:30:00-04:00"),
+    ),
+    (
+        "30 14 * * *",
+        "2024-03-12T14:29:59-04:00",
+        Some("2024-03-12T14:30:00-04:00"),
+    ),
+    // lists
+    (
+        "0,30 * * * *",
+        "2024-03-12T14:10:00-04:00",
+        Some("2024-03-12T14:30:00-04:00"),
+    ),
+    (
+        "0,30 * * * *",
+        "2024-03-12T14:30:00-04:00",
+        Some("2024-03-12T15:00:00-04:00"),
+    ),
+    // ranges
+    (
+        "0 9-17 * * *",
+        "2024-03-12T12:30:00-04:00",
+        Some("2024-03-12T13:00:00-04:00"),
+    ),
+    (
+        "0 9-17 * * *",
+        "2024-03-12T17:00:00-04:00",
+        Some("2024-03-13T09:00:00-04:00"),
+    ),
+    // steps
+    (
+        "*/15 * * * *",
+        "2024-03-12T14:31:00-04:00",
+        Some("2024-03-12T14:45:00-04:00"),
+    ),
+    (
+        "5/20 * * * *",
+        "2024-03-12T14:26:00-04:00",
+        Some("2024-03-12T14:45:00-04:00"),
+    ),
+    (
+        "0 8-18/4 * * *",
+        "2024-03-12T12:00:00-04:00",
+        Some("2024-03-12T16:00:00-04:00"),
+    ),
+    (
+        "0 8-18/4 * * *",
+        "2024-03-12T16:00:00-04:00",
+        Some("2024-03-13T08:00:00-04:00"),
+    ),
+    (
+        "0 0 1 */5 *",
+        "2024-03-12T14:30:00-04:00",
+        Some("2024-06-01T00:00:00-04:00"),
+    ),
+    // months
+    (
+        "0 0 1 1 *",
+        "2024-03-12T14:30:00-04:00",
+        Some("2025-01-01T00:00:00-05:00"),
+    ),
+    (
+        "0 0 29 2 *",
+        "2024-03-12T14:30:00-04:00",
+        Some("2028-02-29T00:00:00-05:00"),
+    ),
+    ("0 0 30 2 *", "2024-03-12T14:30:00-04:00", None),
+    // both 0 and 7 are Sunday
+    (
+        "0 12 * * 0",
+        "2024-03-12T14:30:00-04:00",
+        Some("2024-03-17T12:00:00-04:00"),
+    ),
+    (
+        "0 12 * * 7",
+        "2024-03-12T14:30:00-04:00",
+        Some("2024-03-17T12:00:00-04:00"),
+    ),
+    (
+        "0 12 * * 5-7",
+        "2024-03-15T12:00:00-04:00",
+        Some("2024-03-16T12:00:00-04:00"),
+    ),
+    (
+        "0 12 * * 5-7",
+        "2024-03-17T12:00:00-04:00",
+        Some("2024-03-22T12:00:00-04:00"),
+    ),
+    // day of month or day of week when both are restricted...
+    (
+        "0 12 13 * 5",
+        "2024-03-12T14:30:00-04:00",
+        Some("2024-03-13T12:00:00-04:00"),
+    ),
+    (
+        "0 12 13 * 5",
+        "2024-03-13T12:00:00-04:00",
+        Some("2024-03-15T12:00:00-04:00"),
+    ),
+    // ...but only the restricted one otherwise
+    (
+        "0 12 13 * *",
+        "2024-03-13T12:00:00-04:00",
+        Some("2024-04-13T12:00:00-04:00"),
+    ),
+    (
+        "0 12 */1 * 5",
+        "2024-03-12T14:30:00-04:00",
+        Some("2024-03-15T12:00:00-04:00"),
+    ),
+    // 02:00 to 02:59 don't exist on 2024-03-10
+    (
+        "30 2 * * *",
+        "2024-03-10T00:00:00-05:00",
+        Some("2024-03-11T02:30:00-04:00"),
+    ),
+    (
+        "* * * * *",
+        "2024-03-10T01:59:00-05:00",
+        Some("2024-03-10T03:00:00-04:00"),
+    ),
+    // 01:00 to 01:59 happen twice on 2024-11-03
+    (
+        "30 1 * * *",
+        "2024-11-03T00:00:00-04:00",
+        Some("2024-11-03T01:30:00-04:00"),
+    ),
+    (
+        "30 1 * * *",
+        "2024-11-03T01:30:00-04:00",
+        Some("2024-11-04T01:30:00-05:00"),
+    ),
+    (
+        "30 1 * * *",
+        "2024-11-03T01:10:00-05:00",
+        Some("2024-11-03T01:30:00-05:00"),
+    ),
+];
+
+const INVALID: &[&str] = &[
+    "",
+    "* * * *",
+    "* * * * * *",
+    "60 * * * *",
+    "* 24 * * *",
+    "* * 0 * *",
+    "* * 32 * *",
+    "* * * 0 *",
+    "* * * 13 *",
+    "* * * * 8",
+    "*/0 * * * *",
+    "5-1 * * * *",
+    "a * * * *",
+    "1- * * * *",
+    "-1 * * * *",
+    "1,,2 * * * *",
+    "*/x * * * *",
+    "0 4 * * mon",
+];
+
+/// Parses a time in New York, which has DST. chrono reads `TZ` whenever it converts to local time,
+/// and every test sets the same one.
+fn new_york(time: &str) -> DateTime<Local> {
+    std::env::set_var("TZ", "America/New_York");
+    DateTime::parse_from_rfc3339(time).unwrap().into()
+}
+
+fn schedule(cron: Option<&str>, every_hours: Option<u64>) -> ScheduleConfig {
+    ScheduleConfig {
+        action: ScheduledAction::Kill,
+        cron: cron.map(str::to_string),
+        every_hours,
+        only_when_paused: false,
+        only_when_idle: false,
+    }
+}
+
+/// Always says the same thing.
+struct FakePlayback(PlaybackState);
+
+impl PlaybackDetector for FakePlayback {
+    fn playback_state(&self) -> PlaybackState {
+        self.0
+    }
+}
+
+#[test]
+fn next_runs() {
+    for &(cron, after, expected) in NEXT_RUNS {
+        let expr: CronExpr = cron.parse().unwrap();
+        let next = expr.next_after(new_york(after));
+        let expected = expected.map(|time| DateTime::parse_from_rfc3339(time).unwrap());
+        assert_eq!(
+            next.map(|next| next.fixed_offset()),
+            expected,
+            "{cron:?} after {after}"
+        );
+    }
+}
+
+#[test]
+fn sunday_is_0_or_7() {
+    let sunday: CronExpr = "0 12 * * 0".parse().unwrap();
+    assert_eq!("0 12 * * 7".parse::<CronExpr>().unwrap(), sunday);
+    assert_eq!("0 12 * * 0,7".parse::<CronExpr>().unwrap(), sunday);
+}
+
+#[test]
+fn rejects_invalid_expressions() {
+    for cron in INVALID {
+        assert!(cron.parse::<CronExpr>().is_err(), "{cron:?} was accepted");
+    }
+}
+
+#[test]
+fn rejects_invalid_schedules() {
+    let now = new_york("2024-03-12T14:30:00-04:00");
+    for config in [
+        schedule(Some("0 4 * * *"), Some(6)),
+        schedule(None, None),
+        schedule(None, Some(0)),
+        schedule(Some("0 4 * *"), None),
+    ] {
+        assert!(
+            Scheduler::new(std::slice::from_ref(&config), now).is_err(),
+            "{config:?}"
+        );
+    }
+}
+
+#[test]
+fn runs_due_jobs() {
+    let now = new_york("2024-03-12T14:30:00-04:00");
+    let mut scheduler = Scheduler::new(
+        &[schedule(Some("0 4 * * *"), None), schedule(None, Some(6))],
+        now,
+    )
+    .unwrap();
+    let next_runs = |scheduler: &Scheduler| -> Vec<Option<DateTime<FixedOffset>>> {
+        scheduler
+            .jobs()
+            .iter()
+            .map(|job| job.next_run.map(|next| next.fixed_offset()))
+            .collect()
+    };
+    let at = |time| Some(new_york(time).fixed_offset());
+    assert_eq!(
+        next_runs(&scheduler),
+        [
+            at("2024-03-13T04:00:00-04:00"),
+            at("2024-03-12T20:30:00-04:00")
+        ]
+    );
+    assert_eq!(
+        scheduler.next_wakeup(),
+        Some(new_york("2024-03-12T20:30:00-04:00"))
+    );
+
+    let before = new_york("2024-03-12T20:29:00-04:00");
+    assert!(!scheduler.has_due_jobs(before));
+    assert!(scheduler.due_jobs(before).is_empty());
+    let now = new_york("2024-03-12T20:30:00-04:00");
+    assert!(scheduler.has_due_jobs(now));
+    assert_eq!(scheduler.due_jobs(now), [1]);
+    scheduler.complete(1, now);
+    assert_eq!(next_runs(&scheduler)[1], at("2024-03-13T02:30:00-04:00"));
+}
+
+#[test]
+fn skips_the_next_run() {
+    let now = new_york("2024-03-12T14:30:00-04:00");
+    let mut scheduler = Scheduler::new(&[schedule(Some("0 4 * * *"), None)], now).unwrap();
+
+    // toggling twice doesn't skip
+    scheduler.toggle_skip(0);
+    scheduler.toggle_skip(0);
+    assert!(!scheduler.jobs()[0].skip_next);
+
+    scheduler.toggle_skip(0);
+    assert!(scheduler.jobs()[0].skip_next);
+    // a skipped run is still due, to move it on
+    assert!(scheduler.has_due_jobs(new_york("2024-03-13T04:00:00-04:00")));
+    assert!(scheduler
+        .due_jobs(new_york("2024-03-13T04:00:00-04:00"))
+        .is_empty());
+    let job = &scheduler.jobs()[0];
+    assert!(!job.skip_next);
+    assert_eq!(job.next_run, Some(new_york("2024-03-14T04:00:00-04:00")));
+
+    // only the next run is skipped
+    assert_eq!(
+        scheduler.due_jobs(new_york("2024-03-14T04:00:00-04:00")),
+        [0]
+    );
+}
+
+#[test]
+fn postpones() {
+    let now = new_york("2024-03-13T04:00:00-04:00");
+    let mut scheduler = Scheduler::new(
+        &[schedule(Some("0 4 * * *"), None)],
+        new_york("2024-03-12T14:30:00-04:00"),
+    )
+    .unwrap();
+    assert_eq!(scheduler.due_jobs(now), [0]);
+
+    scheduler.postpone(0, now);
+    assert_eq!(
+        scheduler.jobs()[0].next_run,
+        Some(new_york("2024-03-13T04:01:00-04:00"))
+    );
+    assert!(scheduler
+        .due_jobs(new_york("2024-03-13T04:00:30-04:00"))
+        .is_empty());
+    let now = new_york("2024-03-13T04:01:00-04:00");
+    assert_eq!(scheduler.due_jobs(now), [0]);
+
+    // running it moves on to the next day, not a minute later
+    scheduler.complete(0, now);
+    assert_eq!(
+        scheduler.jobs()[0].next_run,
+        Some(new_york("2024-03-14T04:00:00-04:00"))
+    );
+}
+
+#[test]
+fn only_when_idle() {
+    let threshold = Duration::from_secs(10 * 60);
+    let minutes = |minutes: u64| Some(Duration::from_secs(minutes * 60));
+    let idle_schedule = ScheduleConfig {
+        only_when_idle: true,
+        ..schedule(Some("0 4 * * *"), None)
+    };
+    let playing = FakePlayback(PlaybackState::Playing);
+
+    // the user is active
+    assert!(!idle_schedule.conditions_met(minutes(2), threshold, &playing));
+    // not knowing doesn't count as idle
+    assert!(!idle_schedule.conditions_met(None, threshold, &playing));
+    assert!(idle_schedule.conditions_met(minutes(10), threshold, &playing));
+    assert!(idle_schedule.conditions_met(minutes(60), threshold, &playing));
+
+    // without the condition, being active doesn't matter
+    let unconditional = schedule(Some("0 4 * * *"), None);
+    assert!(unconditional.conditions_met(minutes(0), threshold, &playing));
+}
+
+#[test]
+fn only_when_idle_or_paused() {
+    let threshold = Duration::from_secs(10 * 60);
+    let active = Some(Duration::ZERO);
+    let idle = Some(threshold);
+    let config = ScheduleConfig {
+        only_when_idle: true,
+        only_when_paused: true,
+        ..schedule(Some("0 4 * * *"), None)
+    };
+    let playing = FakePlayback(PlaybackState::Playing);
+    let paused = FakePlayback(PlaybackState::Paused);
+
+    // either is enough
+    assert!(config.conditions_met(idle, threshold, &playing));
+    assert!(config.conditions_met(active, threshold, &paused));
+    assert!(!config.conditions_met(active, threshold, &playing));
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Local, TimeZone, Timelike};
+use spotikill::snooze::{Snooze, SnoozeLength};
+
+fn at(hour: u32, minute: u32) -> DateTime<Local> {
+    Local
+        .with_ymd_and_hms(2024, 3, 12, hour, minute, 0)
+        .single()
+        .unwrap()
+}
+
+#[test]
+fn lengths() {
+    let now = at(14, 30);
+    assert_eq!(
+        Snooze::start(SnoozeLength::Hour, now),
+        Snooze::Until(at(15, 30))
+    );
+    assert_eq!(
+        Snooze::start(SnoozeLength::Indefinitely, now),
+        Snooze::Indefinitely
+    );
+
+    let Snooze::Until(tomorrow) = Snooze::start(SnoozeLength::UntilTomorrow, now) else {
+        panic!("until tomorrow should end");
+    };
+    assert_eq!(tomorrow.date_naive(), now.date_naive().succ_opt().unwrap());
+    assert_eq!((tomorrow.hour(), tomorrow.minute()), (0, 0));
+}
+
+#[test]
+fn runs_out() {
+    let snooze = Snooze::Until(at(15, 30));
+    assert!(snooze.is_active(at(15, 29)));
+    assert!(!snooze.is_active(at(15, 30)));
+    assert_eq!(snooze.at(at(15, 29)), snooze);
+    assert_eq!(snooze.at(at(16, 0)), Snooze::Off);
+
+    assert!(Snooze::Indefinitely.is_active(at(23, 59)));
+    assert!(!Snooze::Off.is_active(at(0, 0)));
+}
+
+#[test]
+fn describes_itself() {
+    let now = at(14, 30);
+    assert_eq!(
+        Snooze::Until(at(15, 30)).describe(now).as_deref(),
+        Some("Snoozed until 15:30")
+    );
+    let tomorrow = Snooze::start(SnoozeLength::UntilTomorrow, now);
+    assert_eq!(
+        tomorrow.describe(now).as_deref(),
+        Some("Snoozed until Wed 00:00")
+    );
+    assert_eq!(
+        Snooze::Indefinitely.describe(now).as_deref(),
+        Some("Snoozed")
+    );
+    assert_eq!(Snooze::Until(at(14, 0)).describe(now), None);
+    assert_eq!(Snooze::Off.describe(now), None);
+}
+
+#[test]
+fn persists() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data").join("snooze.toml");
+    assert_eq!(Snooze::read(&path).unwrap(), Snooze::Off);
+
+    for snooze in [Snooze::Until(at(15, 30)), Snooze::Indefinitely] {
+        snooze.write(&path).unwrap();
+        assert_eq!(Snooze::read(&path).unwrap(), snooze);
+    }
+
+    Snooze::Off.write(&path).unwrap();
+    assert!(!path.exists());
+    // resuming when not snoozed is fine too
+    Snooze::Off.write(&path).unwrap();
+}
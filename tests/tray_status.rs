@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use image::{Rgba, RgbaImage};
+use spotikill::{
+    config::Config,
+    icon::{greyed_out, with_badge, with_pause_badge, IconSet},
+    process::{SpotifyHealth, SpotifyStatus},
+};
+
+const MB: u64 = 1024 * 1024;
+
+/// `(status, tray text, health)` with the default warning thresholds of 2048 MB and 80% CPU
+type Case = (SpotifyStatus, &'static str, SpotifyHealth);
+
+const STATUSES: &[Case] = &[
+    (
+        SpotifyStatus {
+            process_count: 0,
+            memory: 0,
+            cpu_usage: 0.0,
+            unresponsive_count: 0,
+        },
+        "Spotify is not running",
+        SpotifyHealth::NotRunning,
+    ),
+    (
+        SpotifyStatus {
+            process_count: 1,
+            memory: 300 * MB,
+            cpu_usage: 2.5,
+            unresponsive_count: 0,
+        },
+        "Spotify: 1 process, 300.0 MB RAM, 2.5% CPU",
+        SpotifyHealth::Healthy,
+    ),
+    (
+        SpotifyStatus {
+            process_count: 7,
+            memory: 2048 * MB,
+            cpu_usage: 80.0,
+            unresponsive_count: 0,
+        },
+        "Spotify: 7 processes, 2.0 GB RAM, 80.0% CPU",
+        SpotifyHealth::Healthy,
+    ),
+    (
+        SpotifyStatus {
+            process_count: 7,
+            memory: 2049 * MB,
+            cpu_usage: 10.0,
+            unresponsive_count: 0,
+        },
+        "Spotify: 7 processes, 2.0 GB RAM, 10.0% CPU",
+        SpotifyHealth::Unhealthy,
+    ),
+    (
+        SpotifyStatus {
+            process_count: 7,
+            memory: 900 * MB,
+            cpu_usage: 150.5,
+            unresponsive_count: 0,
+        },
+        "Spotify: 7 processes, 900.0 MB RAM, 150.5% CPU",
+        SpotifyHealth::Unhealthy,
+    ),
+    (
+        SpotifyStatus {
+            process_count: 7,
+            memory: 900 * MB,
+            cpu_usage: 0.0,
+            unresponsive_count: 1,
+        },
+        "Spotify: 7 processes, 900.0 MB RAM, 0.0% CPU, 1 not responding",
+        SpotifyHealth::Unhealthy,
+    ),
+];
+
+fn base() -> RgbaImage {
+    RgbaImage::from_pixel(32, 32, Rgba([30, 215, 96, 255]))
+}
+
+#[test]
+fn status_text_and_health() {
+    let config = Config::default();
+    for (status, text, health) in STATUSES {
+        assert_eq!(status.to_string(), *text);
+        assert_eq!(status.health(&config), *health, "{text}");
+    }
+}
+
+#[test]
+fn icon_for_each_health() {
+    let base = base();
+    let icons = IconSet::new(base.clone());
+    let expected = [
+        (SpotifyHealth::Healthy, base.clone()),
+        (SpotifyHealth::Unhealthy, with_badge(&base)),
+        (SpotifyHealth::NotRunning, greyed_out(&base)),
+    ];
+    for (health, icon) in expected {
+        assert_eq!(icons.for_health(health), &icon, "{health:?}");
+        assert_eq!(icons.for_state(health, false), &icon, "{health:?}");
+        assert_eq!(
+            icons.for_state(health, true),
+            &with_pause_badge(&icon),
+            "{health:?} snoozed"
+        );
+    }
+}
+
+#[test]
+fn huge_config_values_saturate() {
+    let config: Config = toml::from_str(
+        "
+        status_refresh_secs = 9223372036854775807
+        memory_warning_mb = 9223372036854775807
+        idle_minutes = 9223372036854775807
+        update_check_hours = 9223372036854775807
+        ",
+    )
+    .unwrap();
+    let status = SpotifyStatus {
+        process_count: 1,
+        memory: u64::MAX,
+        cpu_usage: 0.0,
+        unresponsive_count: 0,
+    };
+    assert_eq!(status.health(&config), SpotifyHealth::Healthy);
+    assert_eq!(config.idle_threshold(), Duration::from_secs(u64::MAX));
+    assert_eq!(
+        config.update_check_interval(),
+        Duration::from_secs(u64::MAX)
+    );
+    // the next refresh is scheduled as an Instant, which can't be that far off
+    assert_eq!(
+        config.status_refresh_interval(),
+        Duration::from_secs(24 * 60 * 60)
+    );
+}
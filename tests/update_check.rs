@@ -0,0 +1,137 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::Path,
+    thread,
+};
+
+use semver::Version;
+use spotikill::update::{check_for_update, current_version, fetch_manifest};
+
+const MANIFEST: &str = r#"{
+    "version": "0.4.0",
+    "url": "https://example.com/releases/0.4.0",
+    "notes": "Bug fixes"
+}"#;
+
+fn write_manifest(dir: &Path, contents: &str) -> String {
+    let path = dir.join("release.json");
+    fs::write(&path, contents).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn file_url(path: &str) -> String {
+    if cfg!(windows) {
+        format!("file:///{}", path.replace('\\', "/"))
+    } else {
+        format!("file://{path}")
+    }
+}
+
+/// Serves a single HTTP response on a random local port and returns the URL to request.
+fn serve_once(status: &'static str, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        // read the request up to the blank line that ends its headers
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+    });
+    format!("http://127.0.0.1:{port}/release.json")
+}
+
+#[test]
+fn reads_manifest_from_file_url_and_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_manifest(dir.path(), MANIFEST);
+
+    for feed in [file_url(&path), path] {
+        let manifest = fetch_manifest(&feed).unwrap();
+        assert_eq!(manifest.version, Version::new(0, 4, 0));
+        assert_eq!(
+            manifest.url.as_deref(),
+            Some("https://example.com/releases/0.4.0")
+        );
+        assert_eq!(manifest.notes.as_deref(), Some("Bug fixes"));
+    }
+}
+
+#[test]
+fn only_newer_versions_are_updates() {
+    let dir = tempfile::tempdir().unwrap();
+    let feed = write_manifest(dir.path(), MANIFEST);
+
+    let update = check_for_update(&feed, &Version::new(0, 3, 0)).unwrap();
+    assert_eq!(update.map(|m| m.version), Some(Version::new(0, 4, 0)));
+    assert_eq!(
+        check_for_update(&feed, &Version::new(0, 4, 0)).unwrap(),
+        None
+    );
+    assert_eq!(
+        check_for_update(&feed, &Version::new(1, 0, 0)).unwrap(),
+        None
+    );
+    // pre-releases sort before the release itself
+    let pre = Version::parse("0.4.0-beta.1").unwrap();
+    assert!(check_for_update(&feed, &pre).unwrap().is_some());
+}
+
+#[test]
+fn url_and_notes_are_optional() {
+    let dir = tempfile::tempdir().unwrap();
+    let feed = write_manifest(dir.path(), r#"{ "version": "99.0.0" }"#);
+
+    let update = check_for_update(&feed, &current_version())
+        .unwrap()
+        .unwrap();
+    assert_eq!(update.url, None);
+    assert_eq!(update.notes, None);
+}
+
+#[test]
+fn bad_manifests_are_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    for contents in [
+        "",
+        "not json",
+        r#"{ "version": "four" }"#,
+        r#"{ "url": "x" }"#,
+        // the release URL is opened, so it must be a web page
+        r#"{ "version": "0.4.0", "url": "example.com/releases" }"#,
+        r#"{ "version": "0.4.0", "url": "file:///C:/Windows/System32/calc.exe" }"#,
+        r#"{ "version": "0.4.0", "url": "file:///usr/bin/xterm" }"#,
+        r#"{ "version": "0.4.0", "url": "smb://attacker.example/share" }"#,
+        r#"{ "version": "0.4.0", "url": "spotify:track:4uLU6hMCjMI75M1A2tKUQC" }"#,
+        r#"{ "version": "0.4.0", "url": "javascript:alert(1)" }"#,
+    ] {
+        let feed = write_manifest(dir.path(), contents);
+        assert!(fetch_manifest(&feed).is_err(), "accepted {contents:?}");
+    }
+    let missing = dir.path().join("missing.json");
+    assert!(fetch_manifest(&missing.to_string_lossy()).is_err());
+}
+
+#[test]
+fn reads_manifest_over_http() {
+    let feed = serve_once("200 OK", MANIFEST);
+    let update = check_for_update(&feed, &Version::new(0, 3, 0)).unwrap();
+    assert_eq!(update.map(|m| m.version), Some(Version::new(0, 4, 0)));
+}
+
+#[test]
+fn http_errors_are_errors() {
+    let feed = serve_once("404 Not Found", "{}");
+    assert!(fetch_manifest(&feed).is_err());
+}